@@ -21,7 +21,7 @@ fn parsing(c: &mut Criterion) {
     let states = PathBuf::from("state_data.csv");
 
     c.bench_function("tokenize", |b| b.iter(|| tokenizer::tokenize(&areas)));
-    let tokens = tokenizer::tokenize(&areas).unwrap();
+    let (tokens, _) = tokenizer::tokenize(&areas).unwrap();
 
     c.bench_function("parse areas", |b| b.iter(|| parser::parse_areas(&tokens)));
     let (areas, metadata) = match parser::parse_areas(&tokens) {
@@ -79,6 +79,20 @@ fn requirements(c: &mut Criterion) {
         (Enemy::Mantis, 2),
     ]);
     c.bench_function("long combat", |b| b.iter(|| req.is_met(&player, &states, player.max_orbs())));
+
+    player = Player::default();
+    player.inventory.grant(Item::Skill(Skill::Glide), 1);
+    player.inventory.grant(Item::Shard(Shard::TripleJump), 1);
+    player.inventory.grant(Item::Teleporter(Teleporter::Marsh), 1);
+    player.inventory.grant(Item::Resource(Resource::Keystone), 4);
+    let req = Requirement::Or(vec![
+        Requirement::And(vec![Requirement::Skill(Skill::Glide), Requirement::Skill(Skill::Grapple)]),
+        Requirement::And(vec![Requirement::Shard(Shard::TripleJump), Requirement::Teleporter(Teleporter::Burrows)]),
+        Requirement::And(vec![Requirement::Skill(Skill::DoubleJump), Requirement::Water]),
+        Requirement::And(vec![Requirement::Teleporter(Teleporter::Marsh), Requirement::Resource(Resource::Keystone, 6)]),
+        Requirement::And(vec![Requirement::Skill(Skill::Glide), Requirement::Shard(Shard::TripleJump), Requirement::Teleporter(Teleporter::Marsh), Requirement::Resource(Resource::Keystone, 4)]),
+    ]);
+    c.bench_function("item checks", |b| b.iter(|| req.is_met(&player, &states, player.max_orbs())));
 }
 
 fn reach_checking(c: &mut Criterion) {
@@ -101,7 +115,7 @@ fn reach_checking(c: &mut Criterion) {
     }));
     c.bench_function("long reach check", |b| b.iter(|| {
         let mut world = World::new(&graph);
-        world.player.inventory = Pool::preset(&Pathsets::default()).progressions;
+        world.player.inventory = Pool::preset(&Pathsets::default()).progressions.into();
         world.player.inventory.grant(Item::SpiritLight(1), 10000);
         let spawn = world.graph.find_spawn("MarshSpawn.Main").unwrap();
         world.graph.reached_locations(&world.player, spawn, &world.uber_states).unwrap();
@@ -12,7 +12,7 @@ use rand::{
 
 use crate::world::{
     World,
-    graph::Node,
+    graph::{Node, IncrementalReach},
     player::Player,
 };
 use crate::inventory::{Inventory, Item};
@@ -61,6 +61,7 @@ struct WorldContext<'a> {
     world: World<'a>,
     player_name: String,
     spawn: &'a Node,
+    reach: IncrementalReach<'a>,
     placements: Vec<Placement<'a>>,
     placeholders: Vec<&'a Node>,
     collected_preplacements: Vec<usize>,
@@ -435,9 +436,11 @@ fn total_reach_check<'a>(world_index: usize, world: &World<'a>) -> Result<Vec<&'
     let mut total_reachable_count = 0;
 
     let spawn = finished_world.graph.find_spawn(DEFAULT_SPAWN)?;
+    let mut reach = IncrementalReach::new(finished_world.graph, spawn);
 
     loop {
-        let mut reachable_locations = finished_world.graph.reached_locations(&finished_world.player, spawn, &finished_world.uber_states)?;
+        reach.update(&finished_world.player, &finished_world.uber_states);
+        let mut reachable_locations = reach.reached().to_vec();
         let new_reachable_count = reachable_locations.len();
 
         if new_reachable_count > total_reachable_count {
@@ -504,6 +507,7 @@ where
         let spirit_light_rng = SpiritLightAmounts::new(f32::from(world.pool.spirit_light), spirit_light_slots as f32, 0.75, 1.25);
 
         Ok(WorldContext {
+            reach: IncrementalReach::new(world.graph, spawns[world_index]),
             world,
             player_name,
             spawn: spawns[world_index],
@@ -537,8 +541,10 @@ where
         let mut reachable_states = Vec::new();
         let mut unmet = Vec::new();
 
-        for world_context in &world_contexts {
-            let (world_reachable, world_unmet) = world_context.world.graph.reached_and_progressions(&world_context.world.player, world_context.spawn, &world_context.world.uber_states)?;
+        for world_context in &mut world_contexts {
+            world_context.reach.update(&world_context.world.player, &world_context.world.uber_states);
+            let world_reachable = world_context.reach.reached().to_vec();
+            let world_unmet = world_context.reach.progressions();
             reachable_states.push(world_reachable.iter().filter(|&&node| !node.can_place()).cloned().collect::<Vec<_>>());
             reachable.push(world_reachable);
             unmet.push(world_unmet);
@@ -709,10 +715,11 @@ where
                         inventory: world_context.world.player.inventory.merge(inventory),
                         ..world_context.world.player.clone()
                     };
-                    let mut lookahead_reachable = world_context.world.graph.reached_locations(&lookahead_player, world_context.spawn, &world_context.world.uber_states)?;
-                    lookahead_reachable.retain(|&node| node.can_place());
+                    let mut lookahead = world_context.reach.clone();
+                    lookahead.update(&lookahead_player, &world_context.world.uber_states);
+                    let lookahead_count = lookahead.reached().iter().filter(|node| node.can_place()).count();
 
-                    newly_reached += lookahead_reachable.len() - reachable_counts[world_index];
+                    newly_reached += lookahead_count - reachable_counts[world_index];
                 }
 
                 if slots < 4 && newly_reached == 0 {
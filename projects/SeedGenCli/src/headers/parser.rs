@@ -412,7 +412,7 @@ pub fn parse_pickup(pickup: &str, shop: bool) -> Result<Item, String> {
         "11" => parse_bonus_upgrade(parts, shop),
         "12" => parse_zone_hint(parts, shop),
         "13" => parse_checkable_hint(parts, shop),
-        _ => Err(String::from("invalid pickup type")),
+        _ => Item::from_name(pickup).ok_or_else(|| String::from("invalid pickup type or item name")),
     }.map_err(|err| format!("{} in pickup {}", err, pickup))
 }
 
@@ -618,14 +618,20 @@ where R: Rng + ?Sized
             processed += ignored;
             processed.push('\n');
         } else {
+            let mut named_line = None;
             if !trimmed.is_empty() {
                 let mut parts = trimmed.splitn(3, '|');
                 let uber_group = parts.next().unwrap();
                 let uber_id = parts.next().ok_or_else(|| format!("malformed pickup {}", trimmed))?;
                 let uber_state = UberState::from_parts(uber_group, uber_id)?;
 
-                let item = parts.next().ok_or_else(|| format!("malformed pickup {}", trimmed))?;
-                let item = parse_pickup(item, uber_state.is_shop())?;
+                let pickup = parts.next().ok_or_else(|| format!("malformed pickup {}", trimmed))?;
+                let item = parse_pickup(pickup, uber_state.is_shop())?;
+
+                // the game only understands codes, so items given by name are written as their code
+                if !pickup.trim_start().starts_with(|char: char| char.is_ascii_digit()) {
+                    named_line = Some(line.replacen(pickup.trim(), &item.code(), 1));
+                }
 
                 // if someone sets an uberstate on spawn, they probably don't want a pickup placed on it
                 if let Item::UberState(command) = &item {
@@ -658,7 +664,7 @@ where R: Rng + ?Sized
 
                 world.preplace(uber_state, item);
             }
-            processed += &line;
+            processed += named_line.as_ref().unwrap_or(&line);
             processed.push('\n');
         }
     }
@@ -832,6 +838,9 @@ mod tests {
         assert_eq!(world.pool.fillers, expected);
         assert!(world.preplacements.contains_key(&UberState::from_parts("1", "106").unwrap()));
         assert!(!world.preplacements.contains_key(&UberState::from_parts("1", "105").unwrap()));
+
+        let header = parse_header("3|0|Launch  // plando by name\n", &mut Vec::new(), &mut HashSet::default(), &mut HashMap::default(), &mut world, &Pathsets::default(), &mut thread_rng()).unwrap();
+        assert_eq!(header, "3|0|2|8  // plando by name\n\n");
     }
 
     #[test]
@@ -868,5 +877,8 @@ mod tests {
         assert!(parse_pickup("-0|65", false).is_err());
         assert_eq!(parse_pickup("12|11|10", false), Ok(Item::Hint(Hint { zone: Zone::Willow, hint_type: ZoneHintType::All })));
         assert_eq!(parse_pickup("12|11", false), Ok(Item::Hint(Hint { zone: Zone::Willow, hint_type: ZoneHintType::Skills })));
+        assert_eq!(parse_pickup("Launch", false), Ok(Item::Skill(Skill::Launch)));
+        assert_eq!(parse_pickup("ultra grapple", true), Ok(Item::Shard(Shard::UltraGrapple)));
+        assert!(parse_pickup("Launch|8", false).is_err());
     }
 }
@@ -2,7 +2,7 @@ use std::fmt;
 
 use rustc_hash::FxHashMap;
 
-use crate::util::{Resource, Skill, Shard, Teleporter, BonusItem, BonusUpgrade, Hint, Pathsets, Pathset, Command};
+use crate::util::{self, Resource, Skill, Shard, Teleporter, BonusItem, BonusUpgrade, Hint, Pathsets, Pathset, Command};
 
 #[allow(clippy::pub_enum_variant_names)]
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
@@ -209,6 +209,29 @@ impl Item {
         }
     }
 
+    /// Parses an item from its name, like "Launch", "Overflow", "MarshTP" or "200 Spirit Light"
+    ///
+    /// Names shared by several items resolve to skills first, then resources, shards and teleporters. A "Shard" or "TP" suffix picks the others
+    pub fn from_name(name: &str) -> Option<Item> {
+        let normalized = util::normalize_name(name);
+        if let Some(shard) = normalized.strip_suffix("shard") {
+            if let Some(shard) = Shard::from_name(shard) { return Some(Item::Shard(shard)); }
+        }
+        match &normalized[..] {
+            "water" | "cleanwater" => return Some(Item::Water),
+            "spiritlight" => return Some(Item::SpiritLight(1)),
+            _ => {},
+        }
+        if let Some(amount) = normalized.strip_suffix("spiritlight") {
+            return amount.parse().ok().map(Item::SpiritLight);
+        }
+
+        Skill::from_name(name).map(Item::Skill)
+            .or_else(|| Resource::from_name(name).map(Item::Resource))
+            .or_else(|| Shard::from_name(name).map(Item::Shard))
+            .or_else(|| Teleporter::from_name(name).map(Item::Teleporter))
+    }
+
     pub fn code(&self) -> String {
         match self {
             Item::SpiritLight(amount) => format!("0|{}", amount),
@@ -376,11 +399,239 @@ impl From<Vec<(Item, u16)>> for Inventory {
     }
 }
 
+// Skills, shards and teleporters are few enough to fit into bitsets by their order in the enums
+#[inline]
+fn bit(index: u8) -> u32 {
+    1 << index
+}
+
+/// The player's items, laid out so that logic can check them without hashing
+///
+/// Items that logic never checks are kept in a regular [`Inventory`]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct PlayerInventory {
+    skills: u32,
+    shards: u32,
+    teleporters: u32,
+    water: bool,
+    ancestral_light: u16,
+    spirit_light: u16,
+    resources: [u16; 5],
+    other: Inventory,
+}
+impl PlayerInventory {
+    #[inline]
+    pub fn has_skill(&self, skill: Skill) -> bool {
+        self.skills & bit(skill as u8) != 0
+    }
+    #[inline]
+    pub fn has_shard(&self, shard: Shard) -> bool {
+        self.shards & bit(shard as u8) != 0
+    }
+    #[inline]
+    pub fn has_teleporter(&self, teleporter: Teleporter) -> bool {
+        self.teleporters & bit(teleporter as u8) != 0
+    }
+    #[inline]
+    pub fn has_water(&self) -> bool {
+        self.water
+    }
+    #[inline]
+    pub fn resource(&self, resource: Resource) -> u16 {
+        self.resources[resource as usize]
+    }
+    #[inline]
+    pub fn spirit_light(&self) -> u16 {
+        self.spirit_light
+    }
+
+    pub fn grant(&mut self, item: Item, amount: u16) {
+        if item.is_single_instance() && amount > 1 {
+            log::warn!("Granted {} more than once, but that item can only be aquired once...", item);
+        }
+        match item {
+            Item::Skill(Skill::AncestralLight) => {
+                self.ancestral_light += amount;
+                self.skills |= bit(Skill::AncestralLight as u8);
+            },
+            Item::Skill(skill) => self.skills |= bit(skill as u8),
+            Item::Shard(shard) => self.shards |= bit(shard as u8),
+            Item::Teleporter(teleporter) => self.teleporters |= bit(teleporter as u8),
+            Item::Water => self.water = true,
+            Item::Resource(resource) => self.resources[resource as usize] += amount,
+            Item::SpiritLight(stacked_amount) => self.spirit_light += amount * stacked_amount,
+            item => self.other.grant(item, amount),
+        }
+    }
+    pub fn remove(&mut self, item: &Item, amount: u16) {
+        match item {
+            Item::Skill(Skill::AncestralLight) => {
+                self.ancestral_light -= amount.min(self.ancestral_light);
+                if self.ancestral_light == 0 { self.skills &= !bit(Skill::AncestralLight as u8); }
+            },
+            Item::Skill(skill) => self.skills &= !bit(*skill as u8),
+            Item::Shard(shard) => self.shards &= !bit(*shard as u8),
+            Item::Teleporter(teleporter) => self.teleporters &= !bit(*teleporter as u8),
+            Item::Water => self.water = false,
+            Item::Resource(resource) => {
+                let owned = &mut self.resources[*resource as usize];
+                *owned -= amount.min(*owned);
+            },
+            Item::SpiritLight(stacked_amount) => self.spirit_light -= (amount * stacked_amount).min(self.spirit_light),
+            item => self.other.remove(item, amount),
+        }
+    }
+
+    pub fn has(&self, item: &Item, amount: u16) -> bool {
+        let owned = self.get(item);
+        owned > 0 && owned >= amount
+    }
+    pub fn get(&self, item: &Item) -> u16 {
+        match item {
+            Item::Skill(Skill::AncestralLight) => self.ancestral_light,
+            Item::Skill(skill) => self.has_skill(*skill).into(),
+            Item::Shard(shard) => self.has_shard(*shard).into(),
+            Item::Teleporter(teleporter) => self.has_teleporter(*teleporter).into(),
+            Item::Water => self.water.into(),
+            Item::Resource(resource) => self.resource(*resource),
+            Item::SpiritLight(stacked_amount) => self.spirit_light / stacked_amount,
+            item => self.other.get(item),
+        }
+    }
+
+    pub fn contains(&self, other: &PlayerInventory) -> bool {
+        self.skills & other.skills == other.skills &&
+        self.shards & other.shards == other.shards &&
+        self.teleporters & other.teleporters == other.teleporters &&
+        (self.water || !other.water) &&
+        self.ancestral_light >= other.ancestral_light &&
+        self.spirit_light >= other.spirit_light &&
+        self.resources.iter().zip(&other.resources).all(|(owned, needed)| owned >= needed) &&
+        self.other.contains(&other.other)
+    }
+
+    #[inline]
+    pub fn contains_mask(&self, mask: &ItemMask) -> bool {
+        self.skills & mask.skills == mask.skills &&
+        self.shards & mask.shards == mask.shards &&
+        self.teleporters & mask.teleporters == mask.teleporters &&
+        (self.water || !mask.water) &&
+        self.spirit_light >= mask.spirit_light &&
+        self.resources.iter().zip(&mask.resources).all(|(owned, needed)| owned >= needed)
+    }
+
+    pub fn merge(&self, other: &Inventory) -> PlayerInventory {
+        let mut merged = self.clone();
+        for (item, amount) in &other.inventory {
+            merged.grant(item.clone(), *amount);
+        }
+        merged
+    }
+
+    /// The same items as a regular [`Inventory`]
+    pub fn to_inventory(&self) -> Inventory {
+        let mut inventory = self.other.clone();
+        for skill in (0..=u8::MAX).filter_map(Skill::from_id).filter(|&skill| skill != Skill::AncestralLight && self.has_skill(skill)) {
+            inventory.grant(Item::Skill(skill), 1);
+        }
+        if self.ancestral_light > 0 { inventory.grant(Item::Skill(Skill::AncestralLight), self.ancestral_light); }
+        for shard in (0..=u8::MAX).filter_map(Shard::from_id).filter(|&shard| self.has_shard(shard)) {
+            inventory.grant(Item::Shard(shard), 1);
+        }
+        for teleporter in (0..=u8::MAX).filter_map(Teleporter::from_id).filter(|&teleporter| self.has_teleporter(teleporter)) {
+            inventory.grant(Item::Teleporter(teleporter), 1);
+        }
+        if self.water { inventory.grant(Item::Water, 1); }
+        for resource in (0..=u8::MAX).filter_map(Resource::from_id).filter(|&resource| self.resource(resource) > 0) {
+            inventory.grant(Item::Resource(resource), self.resource(resource));
+        }
+        if self.spirit_light > 0 { inventory.grant(Item::SpiritLight(1), self.spirit_light); }
+        inventory
+    }
+}
+
+impl fmt::Display for PlayerInventory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_inventory())
+    }
+}
+
+impl From<Inventory> for PlayerInventory {
+    fn from(inventory: Inventory) -> PlayerInventory {
+        PlayerInventory::default().merge(&inventory)
+    }
+}
+
+/// Logic items that are needed together, to check them against a [`PlayerInventory`] at once
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone)]
+pub struct ItemMask {
+    skills: u32,
+    shards: u32,
+    teleporters: u32,
+    water: bool,
+    spirit_light: u16,
+    resources: [u16; 5],
+}
+impl ItemMask {
+    /// Adds an item to the mask, amounts of the same item aren't summed up since each requirement checks them on its own
+    pub fn require(&mut self, item: &Item, amount: u16) {
+        match item {
+            Item::Skill(skill) => self.skills |= bit(*skill as u8),
+            Item::Shard(shard) => self.shards |= bit(*shard as u8),
+            Item::Teleporter(teleporter) => self.teleporters |= bit(*teleporter as u8),
+            Item::Water => self.water = true,
+            Item::Resource(resource) => {
+                let needed = &mut self.resources[*resource as usize];
+                *needed = amount.max(*needed);
+            },
+            Item::SpiritLight(stacked_amount) => self.spirit_light = (amount * stacked_amount).max(self.spirit_light),
+            // logic doesn't check for anything else
+            _ => {},
+        }
+    }
+    pub fn merge(&mut self, other: &ItemMask) {
+        self.skills |= other.skills;
+        self.shards |= other.shards;
+        self.teleporters |= other.teleporters;
+        self.water |= other.water;
+        self.spirit_light = self.spirit_light.max(other.spirit_light);
+        for (needed, other) in self.resources.iter_mut().zip(&other.resources) {
+            *needed = (*other).max(*needed);
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self == &ItemMask::default()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::util::{Zone, ZoneHintType};
 
+    #[test]
+    fn item_names() {
+        assert_eq!(Item::from_name("Launch"), Some(Item::Skill(Skill::Launch)));
+        assert_eq!(Item::from_name("double jump"), Some(Item::Skill(Skill::DoubleJump)));
+        assert_eq!(Item::from_name("Spirit Edge"), Some(Item::Skill(Skill::Sword)));
+        assert_eq!(Item::from_name("OVERFLOW"), Some(Item::Shard(Shard::Overflow)));
+        assert_eq!(Item::from_name("Energy"), Some(Item::Resource(Resource::Energy)));
+        assert_eq!(Item::from_name("Energy Shard"), Some(Item::Shard(Shard::Energy)));
+        assert_eq!(Item::from_name("MarshTP"), Some(Item::Teleporter(Teleporter::Marsh)));
+        assert_eq!(Item::from_name("Burrows Teleporter"), Some(Item::Teleporter(Teleporter::Burrows)));
+        assert_eq!(Item::from_name("burrow"), Some(Item::Skill(Skill::Burrow)));
+        assert_eq!(Item::from_name("200 Spirit Light"), Some(Item::SpiritLight(200)));
+        assert_eq!(Item::from_name("Clean Water"), Some(Item::Water));
+        assert_eq!(Item::from_name("Light Harvest"), Some(Item::Shard(Shard::SpiritLightHarvest)));
+        assert_eq!(Item::from_name("Shard Slot"), Some(Item::Resource(Resource::ShardSlot)));
+        assert_eq!(Item::from_name("Spiderman"), None);
+        assert_eq!(Item::from_name("lots of Spirit Light"), None);
+
+        for item in &[Item::Skill(Skill::AncestralLight), Item::Shard(Shard::LastStand), Item::Teleporter(Teleporter::EastWastes), Item::Resource(Resource::Keystone), Item::SpiritLight(50)] {
+            assert_eq!(Item::from_name(&item.to_string()).as_ref(), Some(item));
+        }
+    }
+
     #[test]
     fn item_display() {
         assert_eq!(Item::SpiritLight(45).code(), "0|45");
@@ -395,4 +646,37 @@ mod tests {
         assert_eq!(Item::Hint(Hint { zone: Zone::Void, hint_type: ZoneHintType::Skills }).code(), "12|12|1");
         assert_eq!(Item::Message(String::from("8|0|9|7")).code(), "6|8|0|9|7");
     }
+
+    #[test]
+    fn player_inventory() {
+        let mut inventory = Inventory::default();
+        inventory.grant(Item::Skill(Skill::Glide), 1);
+        inventory.grant(Item::Skill(Skill::AncestralLight), 2);
+        inventory.grant(Item::Shard(Shard::Magnet), 1);
+        inventory.grant(Item::Teleporter(Teleporter::Marsh), 1);
+        inventory.grant(Item::Resource(Resource::Keystone), 4);
+        inventory.grant(Item::SpiritLight(50), 2);
+        inventory.grant(Item::BonusItem(BonusItem::Relic), 1);
+
+        let mut player_inventory = PlayerInventory::from(inventory.clone());
+        assert!(player_inventory.has_skill(Skill::Glide));
+        assert!(!player_inventory.has_skill(Skill::Grapple));
+        assert!(player_inventory.has_shard(Shard::Magnet));
+        assert!(player_inventory.has_teleporter(Teleporter::Marsh));
+        assert!(!player_inventory.has_water());
+        assert_eq!(player_inventory.resource(Resource::Keystone), 4);
+        assert_eq!(player_inventory.spirit_light(), 100);
+        assert_eq!(player_inventory.get(&Item::Skill(Skill::AncestralLight)), 2);
+        assert!(player_inventory.has(&Item::BonusItem(BonusItem::Relic), 1));
+        assert_eq!(player_inventory.to_inventory(), inventory);
+
+        let before = player_inventory.clone();
+        player_inventory.grant(Item::Water, 1);
+        assert!(player_inventory.contains(&before));
+        assert!(!before.contains(&player_inventory));
+        player_inventory.remove(&Item::Skill(Skill::AncestralLight), 1);
+        assert!(player_inventory.has_skill(Skill::AncestralLight));
+        player_inventory.remove(&Item::Skill(Skill::AncestralLight), 1);
+        assert!(!player_inventory.has_skill(Skill::AncestralLight));
+    }
 }
@@ -1,8 +1,9 @@
 pub mod tokenizer;
 pub mod parser;
 pub mod emitter;
+pub mod formatter;
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use parser::ParseError;
 use crate::world::graph::Graph;
@@ -21,11 +22,12 @@ fn trace_parse_error(areas: &Path, position: usize) -> String {
 }
 
 pub fn parse_logic(areas: &Path, locations: &Path, states: &Path, pathsets: &Pathsets, validate: bool) -> Result<Graph, String> {
-    let tokens = tokenizer::tokenize(areas).map_err(|err| format!("Error parsing areas from {}: {}", areas.display(), err))?;
+    let (tokens, files) = tokenizer::tokenize(areas).map_err(|err| format!("Error parsing areas from {}: {}", areas.display(), err))?;
 
     let (areas, metadata) = parser::parse_areas(&tokens).map_err(|err| {
-        let ParseError { description, position } = err;
-        format!("Error parsing areas.wotw: {}: {}", description, trace_parse_error(areas, position))
+        let ParseError { description, position, file } = err;
+        let file = &files[file];
+        format!("Error parsing {}: {}: {}", file.display(), description, trace_parse_error(file, position))
     })?;
 
     let locations = parser::parse_locations(locations, validate).map_err(|err| format!("Error parsing locations from {}: {}", locations.display(), err))?;
@@ -34,3 +36,154 @@ pub fn parse_logic(areas: &Path, locations: &Path, states: &Path, pathsets: &Pat
 
     emitter::emit(&areas, &metadata, &locations, &state_map, pathsets, validate).map_err(|err| format!("Error building the logic: {}", err))
 }
+
+/// Formats the areas file and all files it includes, returning the files whose formatting changes along with their new contents
+///
+/// Only logic that parses gets formatted
+pub fn format_logic(areas: &Path) -> Result<Vec<(PathBuf, String)>, String> {
+    let (tokens, files) = tokenizer::tokenize(areas).map_err(|err| format!("Error parsing areas from {}: {}", areas.display(), err))?;
+
+    parser::parse_areas(&tokens).map_err(|err| {
+        let ParseError { description, position, file } = err;
+        let file = &files[file];
+        format!("Error parsing {}: {}: {}", file.display(), description, trace_parse_error(file, position))
+    })?;
+
+    let mut changed = Vec::new();
+    for file in files {
+        let input = util::read_file(&file, "logic")?;
+        let formatted = formatter::format(&input);
+        if formatted != input {
+            changed.push((file, formatted));
+        }
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::PathBuf};
+    use crate::world::graph::Node;
+    use crate::util::Pathset;
+
+    fn parse_areas(name: &str, areas: &str, pathsets: &Pathsets) -> Result<Graph, String> {
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, areas).unwrap();
+        let result = parse_logic(&path, &PathBuf::from("loc_data.csv"), &PathBuf::from("state_data.csv"), pathsets, false);
+        fs::remove_file(&path).unwrap();
+        result
+    }
+
+    #[test]
+    fn parameterized_definitions() {
+        let areas = "\
+requirement Wall(health):
+  moki: BreakWall=health
+requirement Wall2(health, energy):
+  moki: Wall(health), Energy=energy
+
+anchor A:
+  conn B:
+    moki: Wall(12)
+anchor B:
+  conn A:
+    moki: Wall2(4, 2) OR Wall(20)
+";
+        let graph = parse_areas("parameterized_definitions.wotw", areas, &Pathsets::default()).unwrap();
+        let requirements = graph.nodes.iter().filter_map(|node| match node {
+            Node::Anchor(anchor) => Some(format!("{:?}", anchor.connections[0].requirement)),
+            _ => None,
+        }).collect::<Vec<_>>();
+        assert_eq!(requirements, [
+            "BreakWall(12.0)",
+            "Or([And([BreakWall(4.0), Resource(Energy, 2)]), BreakWall(20.0)])",
+        ]);
+
+        let wrong_arity = areas.replace("Wall(12)", "Wall(12, 3)");
+        let error = parse_areas("wrong_arity.wotw", &wrong_arity, &Pathsets::default()).unwrap_err();
+        assert!(error.contains("Definition Wall expects 1 arguments, found 2"), "{}", error);
+        let missing_arguments = areas.replace("Wall(12)", "Wall");
+        assert!(parse_areas("missing_arguments.wotw", &missing_arguments, &Pathsets::default()).is_err());
+        let unknown_parameter = areas.replace("BreakWall=health", "BreakWall=energy");
+        assert!(parse_areas("unknown_parameter.wotw", &unknown_parameter, &Pathsets::default()).is_err());
+    }
+
+    #[test]
+    fn declared_pathsets() {
+        let areas = "\
+pathset kii extends gorlek: Harder than gorlek
+pathset launchswap extends kii
+
+anchor A:
+  conn B:
+    kii: Bash
+    launchswap: Launch
+    gorlek: Glide
+anchor B:
+  conn A: free
+";
+        let requirement = |pathsets: &Pathsets| {
+            let graph = parse_areas("declared_pathsets.wotw", areas, pathsets).unwrap();
+            let connection = graph.nodes.iter().find_map(|node| match node {
+                Node::Anchor(anchor) => Some(format!("{:?}", anchor.connections[0].requirement)),
+                _ => None,
+            }).unwrap();
+            (graph, connection)
+        };
+
+        let (graph, connection) = requirement(&Pathsets::default());
+        assert_eq!(connection, "Impossible");
+        assert_eq!(graph.pathsets.len(), 2);
+        assert_eq!(graph.pathsets[0].description, "Harder than gorlek");
+
+        let mut pathsets = Pathsets::default();
+        pathsets.declare("LaunchSwap");
+        let (graph, connection) = requirement(&pathsets);
+        assert_eq!(connection, "Or([Skill(Bash), Skill(Launch), Skill(Glide)])");
+        pathsets.resolve(&graph.pathsets).unwrap();
+        assert!(pathsets.contains(Pathset::Gorlek));
+        assert!(pathsets.contains_declared("kii"));
+
+        pathsets.declare("wavedash");
+        assert!(parse_areas("unknown_pathset.wotw", areas, &pathsets).is_err());
+        let unknown_parent = areas.replace("extends kii", "extends kiii");
+        assert!(parse_areas("unknown_parent.wotw", &unknown_parent, &Pathsets::default()).is_err());
+    }
+
+    #[test]
+    fn includes() {
+        let directory = std::env::temp_dir().join("seedgen_includes");
+        fs::create_dir_all(directory.join("regions")).unwrap();
+        fs::write(directory.join("regions/b.wotw"), "# the B region\nanchor B:\n  conn A:\n    moki: Wall\n").unwrap();
+        let areas = "\
+include \"definitions.wotw\"
+include \"regions/b.wotw\"
+
+anchor A:
+  conn B: free
+";
+        let path = directory.join("areas.wotw");
+        fs::write(&path, areas).unwrap();
+        let parse = || parse_logic(&path, &PathBuf::from("loc_data.csv"), &PathBuf::from("state_data.csv"), &Pathsets::default(), false);
+
+        fs::write(directory.join("definitions.wotw"), "requirement Wall:\n  moki: BreakWall=10\n").unwrap();
+        let graph = parse().unwrap();
+        assert_eq!(graph.nodes.iter().filter(|node| matches!(node, Node::Anchor(_))).count(), 2);
+
+        // a definitions file shared by several regions is only read once
+        fs::write(directory.join("regions/b.wotw"), "include \"../definitions.wotw\"\nanchor B:\n  conn A:\n    moki: Wall\n").unwrap();
+        let graph = parse().unwrap();
+        assert_eq!(graph.nodes.iter().filter(|node| matches!(node, Node::Anchor(_))).count(), 2);
+        fs::write(directory.join("regions/b.wotw"), "# the B region\nanchor B:\n  conn A:\n    moki: Wall\n").unwrap();
+
+        fs::write(directory.join("definitions.wotw"), "requirement Wall:\n  moki: BreakWall=ten\n").unwrap();
+        let error = parse().unwrap_err();
+        assert!(error.contains("definitions.wotw: Need an integer in Requirement at line 2"), "{}", error);
+
+        fs::write(directory.join("definitions.wotw"), "include \"areas.wotw\"\n").unwrap();
+        assert!(parse().unwrap_err().contains("includes itself"));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -1,22 +1,36 @@
 use rustc_hash::{FxHashSet, FxHashMap};
+use smallvec::SmallVec;
 
 use super::parser::{self, AreaTree, Metadata, Location, NamedState};
 use crate::world::{
     graph::{self, Graph, Node},
-    requirements::Requirement,
+    requirements::{Requirement, compiled::CompiledRequirements},
 };
-use crate::util::{Pathsets, Pathset, Skill, Position};
+use crate::util::{Pathsets, Pathset, DeclaredPathset, Skill, Position};
 
-fn build_requirement<'a>(requirement: &parser::Requirement<'a>, definitions: &FxHashMap<&'a str, parser::Group<'a>>, pathsets: &Pathsets, validate: bool, node_map: &FxHashMap::<&'a str, usize>, used_states: &mut FxHashSet<&'a str>) -> Requirement {
+fn build_requirement<'a>(requirement: &parser::Requirement<'a>, arguments: &[u16], definitions: &FxHashMap<&'a str, parser::Group<'a>>, pathsets: &Pathsets, validate: bool, node_map: &FxHashMap::<&'a str, usize>, used_states: &mut FxHashSet<&'a str>) -> Requirement {
     match requirement {
         parser::Requirement::Free => Requirement::Free,
-        parser::Requirement::Definition(identifier) => build_requirement_group(&definitions[identifier], definitions, pathsets, validate, node_map, used_states),
+        parser::Requirement::Definition(identifier, call_arguments) => {
+            let call_arguments: SmallVec<[u16; 2]> = call_arguments.iter().map(|argument| match argument {
+                parser::Argument::Value(value) => *value,
+                parser::Argument::Parameter(index) => arguments[*index],
+            }).collect();
+            build_requirement_group(&definitions[identifier], &call_arguments, definitions, pathsets, validate, node_map, used_states)
+        },
+        parser::Requirement::Parameter(requirement, index) => build_requirement(&requirement.with_amount(arguments[*index]), arguments, definitions, pathsets, validate, node_map, used_states),
         parser::Requirement::Pathset(pathset) =>
             if pathsets.contains(*pathset) {
                 Requirement::Free
             } else {
                 Requirement::Impossible
             },
+        parser::Requirement::DeclaredPathset(pathset) =>
+            if pathsets.contains_declared(pathset) {
+                Requirement::Free
+            } else {
+                Requirement::Impossible
+            },
         parser::Requirement::Skill(skill) => Requirement::Skill(*skill),
         parser::Requirement::EnergySkill(skill, amount) => Requirement::EnergySkill(*skill, (*amount).into()),
         parser::Requirement::SpiritLight(amount) => Requirement::SpiritLight(*amount),
@@ -112,19 +126,19 @@ fn build_or(mut ors: Vec<Requirement>) -> Requirement {
     Requirement::Or(ors)
 }
 
-fn build_requirement_group<'a>(group: &parser::Group<'a>, definitions: &FxHashMap<&'a str, parser::Group<'a>>, pathsets: &Pathsets, validate: bool, node_map: &FxHashMap::<&'a str, usize>, used_states: &mut FxHashSet<&'a str>) -> Requirement {
+fn build_requirement_group<'a>(group: &parser::Group<'a>, arguments: &[u16], definitions: &FxHashMap<&'a str, parser::Group<'a>>, pathsets: &Pathsets, validate: bool, node_map: &FxHashMap::<&'a str, usize>, used_states: &mut FxHashSet<&'a str>) -> Requirement {
     let lines: Vec<Requirement> = group.lines.iter().map(|line| {
         let mut parts = vec![];
         if !line.ands.is_empty() {
-            let ands: Vec<Requirement> = line.ands.iter().map(|and| build_requirement(and, definitions, pathsets, validate, node_map, used_states)).collect();
+            let ands: Vec<Requirement> = line.ands.iter().map(|and| build_requirement(and, arguments, definitions, pathsets, validate, node_map, used_states)).collect();
             parts.push(build_and(ands));
         }
         if !line.ors.is_empty() {
-            let ors: Vec<Requirement> = line.ors.iter().map(|or| build_requirement(or, definitions, pathsets, validate, node_map, used_states)).collect();
+            let ors: Vec<Requirement> = line.ors.iter().map(|or| build_requirement(or, arguments, definitions, pathsets, validate, node_map, used_states)).collect();
             parts.push(build_or(ors));
         }
         if let Some(subgroup) = &line.group {
-            parts.push(build_requirement_group(subgroup, definitions, pathsets, validate, node_map, used_states));
+            parts.push(build_requirement_group(subgroup, arguments, definitions, pathsets, validate, node_map, used_states));
         }
         build_and(parts)
     }).collect();
@@ -139,12 +153,22 @@ fn add_entry<'a>(graph: &mut FxHashMap<&'a str, usize>, key: &'a str, value: usi
 }
 
 pub fn emit(areas: &AreaTree, metadata: &Metadata, locations: &[Location], state_map: &[NamedState], pathsets: &Pathsets, validate: bool) -> Result<Graph, String> {
+    let declared_pathsets = areas.pathsets.iter().map(|pathset| DeclaredPathset {
+        name: pathset.identifier.to_string(),
+        parent: pathset.parent.map(str::to_string),
+        description: pathset.description.to_string(),
+    }).collect::<Vec<_>>();
+    let mut pathsets = pathsets.clone();
+    pathsets.resolve(&declared_pathsets)?;
+    let pathsets = &pathsets;
+
     let node_count = areas.anchors.len() + locations.len() + metadata.states.len();
     let mut graph = Vec::<Node>::with_capacity(node_count);
     let mut used_states = FxHashSet::default();
     used_states.reserve(metadata.states.len());
     let mut node_map = FxHashMap::<&str, usize>::default();
     node_map.reserve(node_count);
+    let mut requirements = CompiledRequirements::default();
 
     for location in locations {
         let name = &location.name[..];
@@ -199,16 +223,17 @@ pub fn emit(areas: &AreaTree, metadata: &Metadata, locations: &[Location], state
         let region = areas.regions.get(region);
         let mut region_requirement = None;
         if let Some(group) = region {
-            region_requirement = Some(build_requirement_group(&group, &areas.definitions, pathsets, validate, &node_map, &mut used_states));
+            region_requirement = Some(build_requirement_group(&group, &[], &areas.definitions, pathsets, validate, &node_map, &mut used_states));
         }
 
         let refills: Vec<graph::Refill> = anchor.refills.iter().map(|refill| {
             let mut requirement = Requirement::Free;
             if let Some(group) = &refill.requirements {
-                requirement = build_requirement_group(group, &areas.definitions, pathsets, validate, &node_map, &mut used_states);
+                requirement = build_requirement_group(group, &[], &areas.definitions, pathsets, validate, &node_map, &mut used_states);
             }
             graph::Refill {
                 name: refill.name,
+                compiled: requirements.compile(&requirement),
                 requirement,
             }
         }).collect();
@@ -217,7 +242,7 @@ pub fn emit(areas: &AreaTree, metadata: &Metadata, locations: &[Location], state
         for connection in &anchor.connections {
             let mut requirement = Requirement::Free;
             if let Some(group) = &connection.requirements {
-                requirement = build_requirement_group(group, &areas.definitions, pathsets, validate, &node_map, &mut used_states);
+                requirement = build_requirement_group(group, &[], &areas.definitions, pathsets, validate, &node_map, &mut used_states);
                 if let Some(region_requirement) = &region_requirement {
                     requirement = build_and(vec![region_requirement.clone(), requirement]);
                 }
@@ -227,6 +252,7 @@ pub fn emit(areas: &AreaTree, metadata: &Metadata, locations: &[Location], state
 
             connections.push(graph::Connection {
                 to,
+                compiled: requirements.compile(&requirement),
                 requirement,
             });
         }
@@ -270,5 +296,7 @@ pub fn emit(areas: &AreaTree, metadata: &Metadata, locations: &[Location], state
 
     Ok(Graph {
         nodes: graph,
+        requirements,
+        pathsets: declared_pathsets,
     })
 }
@@ -0,0 +1,320 @@
+use smallvec::{SmallVec, smallvec};
+
+const INDENT: &str = "  ";
+const KEYWORDS: [&str; 8] = ["requirement ", "region ", "anchor ", "refill ", "state ", "quest ", "pickup ", "conn "];
+
+#[derive(Debug)]
+enum Line {
+    Code(String, Option<String>),
+    Comment(String),
+    Blank,
+}
+
+#[derive(Debug)]
+struct Entry {
+    level: usize,
+    line: Line,
+}
+
+#[derive(Debug)]
+enum Item {
+    Node(Entry, Vec<Item>),
+    Entry(Entry),
+}
+
+// Everything after the first # outside of quotes
+fn split_comment(line: &str) -> (&str, Option<&str>) {
+    let mut quoted = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return (&line[..index], Some(&line[index + 1..])),
+            _ => {},
+        }
+    }
+    (line, None)
+}
+
+// Splits on delimiters outside of parentheses
+fn split_outside_parentheses(input: &str, is_delimiter: impl Fn(char) -> bool) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0_usize;
+    let mut start = 0;
+    for (index, c) in input.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ if depth == 0 && is_delimiter(c) => {
+                parts.push(&input[start..index]);
+                start = index + c.len_utf8();
+            },
+            _ => {},
+        }
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
+fn format_atom(atom: &str) -> String {
+    match atom.find('(') {
+        Some(open) if atom.ends_with(')') => {
+            let arguments = atom[open + 1..atom.len() - 1].split(',').map(str::trim).collect::<Vec<_>>();
+            format!("{}({})", atom[..open].trim(), arguments.join(", "))
+        },
+        _ => atom.to_string(),
+    }
+}
+
+fn format_requirements(requirements: &str) -> String {
+    split_outside_parentheses(requirements, |c| c == ',').into_iter()
+        .map(|and| {
+            let words = split_outside_parentheses(and, char::is_whitespace).into_iter().filter(|word| !word.is_empty()).collect::<Vec<_>>();
+            words.split(|&word| word == "OR")
+                .map(|or| format_atom(&or.join(" ")))
+                .collect::<Vec<_>>()
+                .join(" OR ")
+        })
+        .filter(|and| !and.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// The requirements after a colon, if there is one
+fn format_group(requirements: &str) -> String {
+    match split_outside_parentheses(requirements, |c| c == ':').split_first() {
+        Some((group, rest)) if !rest.is_empty() => {
+            let rest = format_requirements(&rest.join(":"));
+            let group = format_requirements(group);
+            if rest.is_empty() { format!("{}:", group) } else { format!("{}: {}", group, rest) }
+        },
+        _ => format_requirements(requirements),
+    }
+}
+
+fn format_code(code: &str) -> String {
+    if code.starts_with("include ") {
+        return code.to_string();
+    }
+    if let Some(declaration) = code.strip_prefix("pathset ") {
+        return match declaration.split_once(':') {
+            Some((header, description)) if !description.trim().is_empty() => format!("pathset {}: {}", header.split_whitespace().collect::<Vec<_>>().join(" "), description.trim()),
+            Some((header, _)) => format!("pathset {}:", header.split_whitespace().collect::<Vec<_>>().join(" ")),
+            None => format!("pathset {}", declaration.split_whitespace().collect::<Vec<_>>().join(" ")),
+        };
+    }
+
+    let keyword = match KEYWORDS.iter().find(|keyword| code.starts_with(*keyword)) {
+        Some(keyword) => keyword,
+        None => return format_group(code),
+    };
+    let rest = code[keyword.len()..].trim_start();
+    let end = split_outside_parentheses(rest, |c| c.is_whitespace() || c == ':')[0].len();
+    let mut formatted = format!("{}{}", keyword, format_atom(&rest[..end]));
+    let mut rest = rest[end..].trim_start();
+
+    if let Some(position) = rest.strip_prefix("at ") {
+        let end = position.find(':').unwrap_or(position.len());
+        let coordinates = position[..end].split(',').map(str::trim).collect::<Vec<_>>();
+        formatted += &format!(" at {}", coordinates.join(", "));
+        rest = &position[end..];
+    }
+    if let Some(requirements) = rest.strip_prefix(':') {
+        let requirements = format_requirements(requirements);
+        formatted.push(':');
+        if !requirements.is_empty() {
+            formatted.push(' ');
+            formatted += &requirements;
+        }
+    }
+    formatted
+}
+
+// Comments stay in the deepest block they are indented into, blank lines go with the code after them
+//
+// Anything between a line and the first line indented below it goes into that block
+fn resolve_pending(pending: &mut Vec<(usize, Line)>, entries: &mut Vec<Entry>, previous_stack: &[usize], next: Option<(usize, usize)>) {
+    for (depth, line) in pending.drain(..) {
+        let mut level = previous_stack.iter().rposition(|&indent| indent <= depth).unwrap_or(0);
+        match next {
+            Some((next_depth, next_level)) => {
+                let opens_block = next_level >= previous_stack.len();
+                if depth >= next_depth || opens_block { level = level.max(next_level); }
+                if matches!(line, Line::Blank) { level = next_level; }
+            },
+            None => if matches!(line, Line::Blank) { level = 0; },
+        }
+        entries.push(Entry { level, line });
+    }
+}
+
+// Indentation levels are found the same way the tokenizer finds them
+fn read_entries(input: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut indent_stack: SmallVec<[usize; 8]> = smallvec![0];
+    let mut pending: Vec<(usize, Line)> = Vec::new();
+
+    for line in input.lines() {
+        let (code, comment) = split_comment(line);
+        let depth = line.len() - line.trim_start_matches(' ').len();
+        let code = code.trim();
+
+        if code.is_empty() {
+            let line = match comment {
+                Some(comment) => Line::Comment(comment.trim_end().to_string()),
+                None => Line::Blank,
+            };
+            pending.push((depth, line));
+            continue;
+        }
+
+        let previous_stack = indent_stack.clone();
+        while indent_stack.len() > 1 && *indent_stack.last().unwrap() > depth {
+            indent_stack.pop();
+        }
+        if *indent_stack.last().unwrap() < depth {
+            indent_stack.push(depth);
+        }
+        let level = indent_stack.len() - 1;
+
+        resolve_pending(&mut pending, &mut entries, &previous_stack, Some((depth, level)));
+        entries.push(Entry { level, line: Line::Code(format_code(code), comment.map(|comment| comment.trim_end().to_string())) });
+    }
+    resolve_pending(&mut pending, &mut entries, &indent_stack, None);
+
+    entries
+}
+
+fn build_tree(entries: &mut std::iter::Peekable<std::vec::IntoIter<Entry>>, level: usize) -> Vec<Item> {
+    let mut items = Vec::new();
+    while let Some(entry) = entries.peek() {
+        if entry.level < level { break; }
+        let entry = entries.next().unwrap();
+        if matches!(entry.line, Line::Code(..)) {
+            let children = build_tree(entries, entry.level + 1);
+            items.push(Item::Node(entry, children));
+        } else {
+            items.push(Item::Entry(entry));
+        }
+    }
+    items
+}
+
+fn pathset_rank(item: &Item) -> usize {
+    if let Item::Node(Entry { line: Line::Code(code, _), .. }, _) = item {
+        let first = split_outside_parentheses(code, |c| c == ',' || c == ':')[0];
+        match first {
+            "moki" => return 0,
+            "gorlek" => return 1,
+            "unsafe" => return 2,
+            _ => {},
+        }
+    }
+    3
+}
+
+// Pathset groups go from easiest to hardest, comments above a group move along with it
+fn sort_groups(items: Vec<Item>) -> Vec<Item> {
+    let mut chunks: Vec<Vec<Item>> = Vec::new();
+    let mut pending = Vec::new();
+    for item in items {
+        let is_node = matches!(item, Item::Node(..));
+        pending.push(match item {
+            Item::Node(entry, children) => Item::Node(entry, sort_groups(children)),
+            entry => entry,
+        });
+        if is_node {
+            chunks.push(std::mem::take(&mut pending));
+        }
+    }
+    chunks.sort_by_key(|chunk| pathset_rank(chunk.last().unwrap()));
+    chunks.into_iter().flatten().chain(pending).collect()
+}
+
+fn write_items(items: Vec<Item>, output: &mut Vec<String>) {
+    for item in items {
+        let (entry, children) = match item {
+            Item::Node(entry, children) => (entry, children),
+            Item::Entry(entry) => (entry, Vec::new()),
+        };
+        let indent = INDENT.repeat(entry.level);
+        match entry.line {
+            Line::Code(code, None) => output.push(format!("{}{}", indent, code)),
+            Line::Code(code, Some(comment)) => output.push(format!("{}{}  #{}", indent, code, comment)),
+            Line::Comment(comment) => output.push(format!("{}#{}", indent, comment)),
+            Line::Blank => {
+                if matches!(output.last(), Some(last) if !last.is_empty()) {
+                    output.push(String::new());
+                }
+            },
+        }
+        write_items(children, output);
+    }
+}
+
+/// Formats a logic file in the canonical style
+///
+/// Expects input that tokenizes without errors, the formatter itself doesn't validate anything
+pub fn format(input: &str) -> String {
+    let mut entries = read_entries(input).into_iter().peekable();
+    let tree = sort_groups(build_tree(&mut entries, 0));
+
+    let mut output = Vec::new();
+    write_items(tree, &mut output);
+    while matches!(output.last(), Some(last) if last.is_empty()) {
+        output.pop();
+    }
+
+    let mut formatted = output.join("\n");
+    formatted.push('\n');
+    formatted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formatting() {
+        let input = "\
+# header
+
+
+requirement Wall( health,energy ):
+    moki:   BreakWall=health,Energy=energy
+anchor A   at  1 ,-2:   # comment
+    pickup A.Pickup:free
+    conn B:
+    # about the connection
+        unsafe: Bash
+        # trailing
+        gorlek:
+            Wall(1,2)  OR Sword
+        moki , Regenerate:    Dash
+
+    state A.State:
+        moki: free   #   spaced
+
+";
+        let expected = "\
+# header
+
+requirement Wall(health, energy):
+  moki: BreakWall=health, Energy=energy
+anchor A at 1, -2:  # comment
+  pickup A.Pickup: free
+  conn B:
+    moki, Regenerate: Dash
+    # trailing
+    gorlek:
+      Wall(1, 2) OR Sword
+    # about the connection
+    unsafe: Bash
+
+  state A.State:
+    moki: free  #   spaced
+";
+        let formatted = format(input);
+        assert_eq!(formatted, expected);
+        assert_eq!(format(&formatted), formatted);
+    }
+}
@@ -9,13 +9,23 @@ use crate::util::{self, Pathset, Skill, Resource, Shard, Teleporter, RefillType,
 pub struct ParseError {
     pub description: String,
     pub position: usize,
+    pub file: usize,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
+pub enum Argument {
+    Value(u16),
+    // index into the parameters of the surrounding definition
+    Parameter(usize),
+}
+#[derive(Debug, Clone)]
 pub enum Requirement<'a> {
     Free,
-    Definition(&'a str),
+    Definition(&'a str, SmallVec<[Argument; 2]>),
+    // a requirement whose amount is a parameter of the surrounding definition
+    Parameter(Box<Requirement<'a>>, usize),
     Pathset(Pathset),
+    DeclaredPathset(&'a str),
     Skill(Skill),
     EnergySkill(Skill, u16),
     SpiritLight(u16),
@@ -35,6 +45,26 @@ pub enum Requirement<'a> {
     HammerSentryJump(u16),
     SentryBurn(u16),
 }
+impl Requirement<'_> {
+    /// Replaces the amount of numeric requirements
+    pub fn with_amount(&self, amount: u16) -> Self {
+        match self {
+            Requirement::EnergySkill(skill, _) => Requirement::EnergySkill(*skill, amount),
+            Requirement::SpiritLight(_) => Requirement::SpiritLight(amount),
+            Requirement::Resource(resource, _) => Requirement::Resource(*resource, amount),
+            Requirement::Damage(_) => Requirement::Damage(amount),
+            Requirement::Danger(_) => Requirement::Danger(amount),
+            Requirement::Boss(_) => Requirement::Boss(amount),
+            Requirement::BreakWall(_) => Requirement::BreakWall(amount),
+            Requirement::ShurikenBreak(_) => Requirement::ShurikenBreak(amount),
+            Requirement::SentryJump(_) => Requirement::SentryJump(amount),
+            Requirement::SwordSentryJump(_) => Requirement::SwordSentryJump(amount),
+            Requirement::HammerSentryJump(_) => Requirement::HammerSentryJump(amount),
+            Requirement::SentryBurn(_) => Requirement::SentryBurn(amount),
+            _ => self.clone(),
+        }
+    }
+}
 #[derive(Debug)]
 pub struct Line<'a> {
     pub ands: Vec<Requirement<'a>>,
@@ -64,7 +94,14 @@ pub struct Anchor<'a> {
     pub connections: Vec<Connection<'a>>,
 }
 #[derive(Debug)]
+pub struct PathsetDeclaration<'a> {
+    pub identifier: &'a str,
+    pub parent: Option<&'a str>,
+    pub description: &'a str,
+}
+#[derive(Debug)]
 pub struct AreaTree<'a> {
+    pub pathsets: Vec<PathsetDeclaration<'a>>,
     pub definitions: FxHashMap<&'a str, Group<'a>>,
     pub regions: FxHashMap<&'a str, Group<'a>>,
     pub anchors: Vec<Anchor<'a>>,
@@ -73,10 +110,14 @@ pub struct AreaTree<'a> {
 #[derive(Debug)]
 struct ParseContext {
     position: usize,
+    // parameters of the definition currently being parsed
+    parameters: Vec<String>,
 }
 #[derive(Debug)]
 pub struct Metadata<'a> {
-    definitions: FxHashSet<&'a str>,
+    // names and number of parameters
+    definitions: FxHashMap<&'a str, usize>,
+    pathsets: FxHashSet<&'a str>,
     pub states: FxHashSet<&'a str>,
     pub quests: FxHashSet<&'a str>,
 }
@@ -91,13 +132,81 @@ fn eat(tokens: &[Token], context: &mut ParseContext, expected_token_type: TokenT
     }
 }
 
-fn parse_requirement<'a>(token: &'a Token, metadata: &Metadata) -> Result<Requirement<'a>, ParseError> {
+// Splits "Name(a, b)" into the name and its arguments
+fn split_arguments<'a>(value: &'a str, token: &Token) -> Result<(&'a str, SmallVec<[&'a str; 2]>), ParseError> {
+    match value.split_once('(') {
+        Some((name, arguments)) => {
+            let arguments = arguments.strip_suffix(')').ok_or_else(|| wrong_requirement(token))?;
+            let arguments: SmallVec<[_; 2]> = arguments.split(',').map(str::trim).collect();
+            if arguments.iter().any(|argument| argument.is_empty()) {
+                return Err(wrong_requirement(token));
+            }
+            Ok((name, arguments))
+        },
+        None => Ok((value, SmallVec::new())),
+    }
+}
+
+fn parse_definition_call<'a>(keyword: &'a str, token: &Token, parameters: &[String], metadata: &Metadata) -> Result<Requirement<'a>, ParseError> {
+    let (name, arguments) = split_arguments(keyword, token)?;
+    let arity = *metadata.definitions.get(name).ok_or_else(|| wrong_requirement(token))?;
+    if arguments.len() != arity {
+        return Err(wrong_arity(token, name, arity, arguments.len()));
+    }
+
+    let arguments = arguments.iter().map(|argument| {
+        if let Ok(value) = argument.parse() {
+            Ok(Argument::Value(value))
+        } else if let Some(index) = parameters.iter().position(|parameter| parameter == argument) {
+            Ok(Argument::Parameter(index))
+        } else {
+            Err(not_int(token))
+        }
+    }).collect::<Result<_, _>>()?;
+    Ok(Requirement::Definition(name, arguments))
+}
+
+fn parse_amount<'a>(keyword: &str, amount: u16, token: &Token) -> Result<Requirement<'a>, ParseError> {
+    match keyword {
+        "Blaze" => Ok(Requirement::EnergySkill(Skill::Blaze, amount)),
+        "Boss" => Ok(Requirement::Boss(amount)),
+        "Bow" => Ok(Requirement::EnergySkill(Skill::Bow, amount)),
+        "BreakWall" => Ok(Requirement::BreakWall(amount)),
+        "Damage" => Ok(Requirement::Damage(amount)),
+        "Danger" => Ok(Requirement::Danger(amount)),
+        "Energy" => Ok(Requirement::Resource(Resource::Energy, amount)),
+        "Flash" => Ok(Requirement::EnergySkill(Skill::Flash, amount)),
+        "Grenade" => Ok(Requirement::EnergySkill(Skill::Grenade, amount)),
+        "Health" => Ok(Requirement::Resource(Resource::Health, amount)),
+        "Keystone" => Ok(Requirement::Resource(Resource::Keystone, amount)),
+        "Ore" => Ok(Requirement::Resource(Resource::Ore, amount)),
+        "Sentry" => Ok(Requirement::EnergySkill(Skill::Sentry, amount)),
+        "SentryJump" => Ok(Requirement::SentryJump(amount)),
+        "SwordSJump" => Ok(Requirement::SwordSentryJump(amount)),
+        "HammerSJump" => Ok(Requirement::HammerSentryJump(amount)),
+        "SentryBurn" => Ok(Requirement::SentryBurn(amount)),
+        "ShardSlot" => Ok(Requirement::Resource(Resource::ShardSlot, amount)),
+        "Shuriken" => Ok(Requirement::EnergySkill(Skill::Shuriken, amount)),
+        "ShurikenBreak" => Ok(Requirement::ShurikenBreak(amount)),
+        "Spear" => Ok(Requirement::EnergySkill(Skill::Spear, amount)),
+        "SpiritLight" => Ok(Requirement::SpiritLight(amount)),
+        _ => Err(wrong_requirement(token))
+    }
+}
+
+fn parse_requirement<'a>(token: &'a Token, parameters: &[String], metadata: &Metadata) -> Result<Requirement<'a>, ParseError> {
     let mut parts = token.value.split('=');
     let keyword = parts.next().unwrap();
     let amount = parts.next();
     if parts.next().is_some() {
         return Err(wrong_amount(token));
     }
+    if keyword.contains('(') {
+        if amount.is_some() {
+            return Err(wrong_amount(token));
+        }
+        return parse_definition_call(keyword, token, parameters, metadata);
+    }
     match amount {
         Some(amount) => {
             if keyword == "Combat" {
@@ -149,35 +258,14 @@ fn parse_requirement<'a>(token: &'a Token, metadata: &Metadata) -> Result<Requir
                 }
                 return Ok(Requirement::Combat(enemies));
             }
+            if let Some(index) = parameters.iter().position(|parameter| parameter == amount) {
+                return Ok(Requirement::Parameter(Box::new(parse_amount(keyword, 0, token)?), index));
+            }
             let amount: u16 = match amount.parse() {
                 Ok(result) => result,
                 Err(_) => return Err(not_int(token)),
             };
-            match keyword {
-                "Blaze" => Ok(Requirement::EnergySkill(Skill::Blaze, amount)),
-                "Boss" => Ok(Requirement::Boss(amount)),
-                "Bow" => Ok(Requirement::EnergySkill(Skill::Bow, amount)),
-                "BreakWall" => Ok(Requirement::BreakWall(amount)),
-                "Damage" => Ok(Requirement::Damage(amount)),
-                "Danger" => Ok(Requirement::Danger(amount)),
-                "Energy" => Ok(Requirement::Resource(Resource::Energy, amount)),
-                "Flash" => Ok(Requirement::EnergySkill(Skill::Flash, amount)),
-                "Grenade" => Ok(Requirement::EnergySkill(Skill::Grenade, amount)),
-                "Health" => Ok(Requirement::Resource(Resource::Health, amount)),
-                "Keystone" => Ok(Requirement::Resource(Resource::Keystone, amount)),
-                "Ore" => Ok(Requirement::Resource(Resource::Ore, amount)),
-                "Sentry" => Ok(Requirement::EnergySkill(Skill::Sentry, amount)),
-                "SentryJump" => Ok(Requirement::SentryJump(amount)),
-                "SwordSJump" => Ok(Requirement::SwordSentryJump(amount)),
-                "HammerSJump" => Ok(Requirement::HammerSentryJump(amount)),
-                "SentryBurn" => Ok(Requirement::SentryBurn(amount)),
-                "ShardSlot" => Ok(Requirement::Resource(Resource::ShardSlot, amount)),
-                "Shuriken" => Ok(Requirement::EnergySkill(Skill::Shuriken, amount)),
-                "ShurikenBreak" => Ok(Requirement::ShurikenBreak(amount)),
-                "Spear" => Ok(Requirement::EnergySkill(Skill::Spear, amount)),
-                "SpiritLight" => Ok(Requirement::SpiritLight(amount)),
-                _ => Err(wrong_requirement(token))
-            }
+            parse_amount(keyword, amount, token)
         }
         None => match keyword {
             "Arcing" => Ok(Requirement::Shard(Shard::Arcing)),
@@ -239,7 +327,8 @@ fn parse_requirement<'a>(token: &'a Token, metadata: &Metadata) -> Result<Requir
             "WestWastesTP" => Ok(Requirement::Teleporter(Teleporter::WestWastes)),
             "WestWoodsTP" => Ok(Requirement::Teleporter(Teleporter::WestWoods)),
             "WillowTP" => Ok(Requirement::Teleporter(Teleporter::Willow)),
-            _ if metadata.definitions.contains(keyword) => Ok(Requirement::Definition(keyword)),
+            _ if metadata.pathsets.contains(keyword) => Ok(Requirement::DeclaredPathset(keyword)),
+            _ if metadata.definitions.contains_key(keyword) => parse_definition_call(keyword, token, parameters, metadata),
             _ if metadata.states.contains(keyword) || metadata.quests.contains(keyword) => Ok(Requirement::State(keyword)),
             "Combat" | "Boss" | "BreakWall" | "Damage" | "Danger" | "Energy" | "Health" | "Keystone" | "Ore" | "SentryBurn" | "SentryJump"| "SwordSJump" | "HammerSJump" | "ShardSlot" | "ShurikenBreak" | "SpiritLight"
                 => Err(wrong_amount(token)),
@@ -271,32 +360,32 @@ fn parse_line<'a>(tokens: &'a [Token], context: &mut ParseContext, metadata: &Me
                 match tokens[context.position].name {
                     TokenType::And => {
                         context.position += 1;
-                        ands.push(parse_requirement(token, metadata)?);
+                        ands.push(parse_requirement(token, &context.parameters, metadata)?);
                     },
                     TokenType::Or => {
                         context.position += 1;
-                        ors.push(parse_requirement(token, metadata)?);
+                        ors.push(parse_requirement(token, &context.parameters, metadata)?);
                     },
                     TokenType::Newline => {
                         context.position += 1;
                         if ors.is_empty() {
-                            ands.push(parse_requirement(token, metadata)?);
+                            ands.push(parse_requirement(token, &context.parameters, metadata)?);
                         } else {
-                            ors.push(parse_requirement(token, metadata)?);
+                            ors.push(parse_requirement(token, &context.parameters, metadata)?);
                         }
                         break;
                     },
                     TokenType::Dedent => {
                         if ors.is_empty() {
-                            ands.push(parse_requirement(token, metadata)?);
+                            ands.push(parse_requirement(token, &context.parameters, metadata)?);
                         } else {
-                            ors.push(parse_requirement(token, metadata)?);
+                            ors.push(parse_requirement(token, &context.parameters, metadata)?);
                         }
                         break;
                     },
                     TokenType::Group => {
                         context.position += 1;
-                        ands.push(parse_requirement(token, metadata)?);
+                        ands.push(parse_requirement(token, &context.parameters, metadata)?);
                         if let TokenType::Indent = tokens[context.position].name {
                             context.position += 1;
                             group = Some(parse_group(tokens, context, metadata)?);
@@ -444,12 +533,44 @@ fn parse_region<'a>(tokens: &'a [Token], context: &mut ParseContext, metadata: &
 }
 #[inline]
 fn parse_definition<'a>(tokens: &'a [Token], context: &mut ParseContext, metadata: &Metadata) -> Result<(&'a str, Group<'a>), ParseError> {
-    let (identifier, requirements) = parse_named_group(tokens, context, metadata)?;
+    let token = &tokens[context.position];
+    let (identifier, parameters) = split_arguments(&token.value, token)?;
+    context.parameters = parameters.iter().map(|parameter| parameter.to_string()).collect();
+    let (_, requirements) = parse_named_group(tokens, context, metadata)?;
+    context.parameters.clear();
     Ok((
         identifier,
         requirements,
     ))
 }
+fn parse_pathset<'a>(tokens: &'a [Token], context: &mut ParseContext, metadata: &Metadata) -> Result<PathsetDeclaration<'a>, ParseError> {
+    let token = &tokens[context.position];
+    context.position += 1;
+
+    let (declaration, description) = token.value.split_once(':').unwrap_or((&token.value, ""));
+    let mut words = declaration.split_whitespace();
+    let identifier = words.next().ok_or_else(|| wrong_pathset(token))?;
+    let parent = match (words.next(), words.next(), words.next()) {
+        (None, _, _) => None,
+        (Some("extends"), Some(parent), None) => Some(parent),
+        _ => return Err(wrong_pathset(token)),
+    };
+    if let Some(parent) = parent {
+        if Pathset::from_difficulty(parent).is_none() && !metadata.pathsets.contains(parent) {
+            return Err(ParseError {
+                description: format!("Unknown parent pathset {} at line {}", parent, token.line),
+                position: token.position,
+                file: token.file,
+            });
+        }
+    }
+
+    Ok(PathsetDeclaration {
+        identifier,
+        parent,
+        description: description.trim(),
+    })
+}
 #[inline]
 fn parse_anchor<'a>(tokens: &'a [Token], context: &mut ParseContext, metadata: &Metadata) -> Result<Anchor<'a>, ParseError> {
     let identifier = &tokens[context.position].value;
@@ -503,31 +624,50 @@ fn wrong_token(token: &Token, description: &str) -> ParseError {
     ParseError {
         description: format!("Expected {} at line {}, instead found {:?}", description, token.line, token.name),
         position: token.position,
+        file: token.file,
     }
 }
 fn wrong_amount(token: &Token) -> ParseError {
     ParseError {
         description: format!("Failed to parse amount at line {}", token.line),
         position: token.position,
+        file: token.file,
     }
 }
 fn wrong_requirement(token: &Token) -> ParseError {
     ParseError {
         description: format!("Failed to parse requirement at line {}", token.line),
         position: token.position,
+        file: token.file,
+    }
+}
+fn wrong_pathset(token: &Token) -> ParseError {
+    ParseError {
+        description: format!("Expected pathset declaration like 'pathset name extends parent: description' at line {}", token.line),
+        position: token.position,
+        file: token.file,
+    }
+}
+fn wrong_arity(token: &Token, name: &str, expected: usize, found: usize) -> ParseError {
+    ParseError {
+        description: format!("Definition {} expects {} arguments, found {} at line {}", name, expected, found, token.line),
+        position: token.position,
+        file: token.file,
     }
 }
 fn not_int(token: &Token) -> ParseError {
     ParseError {
         description: format!("Need an integer in {:?} at line {}", token.name, token.line),
         position: token.position,
+        file: token.file,
     }
 }
 
 fn preprocess<'a>(tokens: &'a [Token], context: &mut ParseContext) -> Metadata<'a> {
     // Find all states so we can differentiate states from pathsets.
     let end = tokens.len();
-    let mut definitions = FxHashSet::default();
+    let mut definitions = FxHashMap::default();
+    let mut pathsets = FxHashSet::default();
     let mut states = FxHashSet::default();
     states.reserve(end / 500);
     let mut quests = FxHashSet::default();
@@ -536,7 +676,12 @@ fn preprocess<'a>(tokens: &'a [Token], context: &mut ParseContext) -> Metadata<'
     while context.position < end {
         let token = &tokens[context.position];
         match token.name {
-            TokenType::Definition => { definitions.insert(&token.value[..]); },
+            TokenType::Definition => if let Ok((name, parameters)) = split_arguments(&token.value, token) {
+                definitions.insert(name, parameters.len());
+            },
+            TokenType::Pathset => if let Some(name) = token.value.split(|c: char| c == ':' || c.is_whitespace()).next() {
+                pathsets.insert(name);
+            },
             TokenType::Quest => { quests.insert(&token.value[..]); },
             TokenType::State => { states.insert(&token.value[..]); },
             _ => {},
@@ -547,6 +692,7 @@ fn preprocess<'a>(tokens: &'a [Token], context: &mut ParseContext) -> Metadata<'
 
     Metadata {
         definitions,
+        pathsets,
         states,
         quests,
     }
@@ -554,23 +700,34 @@ fn preprocess<'a>(tokens: &'a [Token], context: &mut ParseContext) -> Metadata<'
 
 fn process<'a>(tokens: &'a [Token], context: &mut ParseContext, metadata: &Metadata) -> Result<AreaTree<'a>, ParseError> {
     let end = tokens.len();
+    let mut pathsets = Vec::<PathsetDeclaration>::new();
     let mut definitions = FxHashMap::default();
     let mut regions = FxHashMap::default();
     regions.reserve(20);
     let mut anchors = Vec::<Anchor>::new();
     anchors.reserve(end / 200);
 
-    if let TokenType::Newline = tokens[context.position].name { context.position += 1 }
-
     while context.position < end {
         let token = &tokens[context.position];
         match token.name {
+            TokenType::Pathset => {
+                let pathset = parse_pathset(tokens, context, metadata)?;
+                if pathsets.iter().any(|other| other.identifier == pathset.identifier) {
+                    return Err(ParseError {
+                        description: format!("Pathset name {} already in use at line {}", pathset.identifier, token.line),
+                        position: token.position,
+                        file: token.file,
+                    });
+                }
+                pathsets.push(pathset);
+            },
             TokenType::Definition => {
                 let (key, value) = parse_definition(tokens, context, metadata)?;
                 if definitions.insert(key, value).is_some() {
                     return Err(ParseError {
                         description: format!("Requirement name {} already in use at line {}", key, token.line),
                         position: token.position,
+                        file: token.file,
                     });
                 }
             },
@@ -580,14 +737,18 @@ fn process<'a>(tokens: &'a [Token], context: &mut ParseContext, metadata: &Metad
                     return Err(ParseError {
                         description: format!("Region name {} already in use at line {}", key, token.line),
                         position: token.position,
+                        file: token.file,
                     });
                 }
             },
             TokenType::Anchor => anchors.push(parse_anchor(tokens, context, metadata)?),
-            _ => return Err(wrong_token(&tokens[context.position], "definition or anchor")),
+            // between included files
+            TokenType::Newline => context.position += 1,
+            _ => return Err(wrong_token(&tokens[context.position], "pathset, definition or anchor")),
         }
     }
     Ok(AreaTree {
+        pathsets,
         definitions,
         regions,
         anchors,
@@ -693,6 +854,7 @@ pub fn parse_states(path: &Path, validate: bool) -> Result<Vec<NamedState>, Stri
 pub fn parse_areas(tokens: &[Token]) -> Result<(AreaTree, Metadata), ParseError> {
     let mut context = ParseContext {
         position: 0,
+        parameters: Vec::new(),
     };
     let metadata = preprocess(tokens, &mut context);
     context.position = 0;
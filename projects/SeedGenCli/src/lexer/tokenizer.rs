@@ -1,5 +1,6 @@
-use std::{cmp::Ordering, path::Path};
+use std::{cmp::Ordering, path::{Path, PathBuf}};
 
+use rustc_hash::FxHashSet;
 use smallvec::{SmallVec, smallvec};
 
 use crate::util;
@@ -24,6 +25,8 @@ pub enum TokenType {
     Group,
     And,
     Or,
+    Include,
+    Pathset,
 }
 #[derive(Debug)]
 pub struct Token {
@@ -31,10 +34,13 @@ pub struct Token {
     pub value: String,
     pub line: usize,
     pub position: usize,
+    // index into the tokenized files
+    pub file: usize,
 }
 struct TokenContext {
     line: usize,
     position: usize,
+    file: usize,
     indent_stack: SmallVec<[usize; 8]>,
 }
 
@@ -80,6 +86,7 @@ fn skip_whitespace(input: &str, context: &mut TokenContext) -> Option<(usize, To
             value: String::new(),
             line: 0,
             position: 0,
+            file: 0,
         }
     ))
 }
@@ -126,16 +133,27 @@ fn tokenize_indent(input: &str, context: &mut TokenContext) -> Option<(usize, To
             value: String::new(),
             line: context.line,
             position: context.position,
+            file: context.file,
         }
     ))
 }
 
+// Arguments in parentheses may contain delimiters
+fn find_delimiter(input: &str, is_delimiter: impl Fn(char) -> bool) -> Option<usize> {
+    let delimiter = input.find(|c: char| c == '(' || is_delimiter(c))?;
+    if input[delimiter..].starts_with('(') {
+        let close = delimiter + input[delimiter..].find(')')? + 1;
+        return Some(close + input[close..].find(is_delimiter)?);
+    }
+    Some(delimiter)
+}
+
 fn tokenize_identifier(input: &str, context: &mut TokenContext, keyword: &str, name: TokenType) -> Option<(usize, Token)> {
     if !input.starts_with(keyword) {
         return None;
     }
     let identifier = &input[keyword.len()..];
-    let delimiter = identifier.find(|c: char| c.is_whitespace() || c == ':' || c == '#')?;
+    let delimiter = find_delimiter(identifier, |c: char| c.is_whitespace() || c == ':' || c == '#')?;
     let mut consume_colon = 0;
     if identifier.bytes().nth(delimiter)? == b':' {
         consume_colon = 1;
@@ -148,6 +166,7 @@ fn tokenize_identifier(input: &str, context: &mut TokenContext, keyword: &str, n
             value: identifier.to_string(),
             line: context.line,
             position: context.position,
+            file: context.file,
         },
     ))
 }
@@ -184,6 +203,39 @@ fn tokenize_connection(input: &str, context: &mut TokenContext) -> Option<(usize
     tokenize_identifier(input, context, "conn ", TokenType::Connection)
 }
 
+#[inline]
+fn tokenize_include(input: &str, context: &mut TokenContext) -> Option<(usize, Token)> {
+    let path = input.strip_prefix("include \"")?;
+    let end = path.find('"')?;
+    Some((
+        "include \"".len() + end + 1,
+        Token {
+            name: TokenType::Include,
+            value: path[..end].to_string(),
+            line: context.line,
+            position: context.position,
+            file: context.file,
+        }
+    ))
+}
+
+// The whole line, since the description is free text
+#[inline]
+fn tokenize_pathset(input: &str, context: &mut TokenContext) -> Option<(usize, Token)> {
+    let declaration = input.strip_prefix("pathset ")?;
+    let end = declaration.find(&['\n', '\r', '#'][..]).unwrap_or(declaration.len());
+    Some((
+        "pathset ".len() + end,
+        Token {
+            name: TokenType::Pathset,
+            value: declaration[..end].trim().to_string(),
+            line: context.line,
+            position: context.position,
+            file: context.file,
+        }
+    ))
+}
+
 #[inline]
 fn tokenize_position(input: &str, context: &mut TokenContext) -> Option<(usize, Token)> {
     if !input.starts_with("at ") {
@@ -203,6 +255,7 @@ fn tokenize_position(input: &str, context: &mut TokenContext) -> Option<(usize,
             value: format!("{},{}", x, y),
             line: context.line,
             position: context.position,
+            file: context.file,
         }
     ))
 }
@@ -219,6 +272,7 @@ fn tokenize_group(input: &str, context: &mut TokenContext) -> Option<(usize, Tok
             value: String::new(),
             line: context.line,
             position: context.position,
+            file: context.file,
         }
     ))
 }
@@ -234,6 +288,7 @@ fn tokenize_and(input: &str, context: &mut TokenContext) -> Option<(usize, Token
             value: String::new(),
             line: context.line,
             position: context.position,
+            file: context.file,
         }
     ))
 }
@@ -249,13 +304,14 @@ fn tokenize_or(input: &str, context: &mut TokenContext) -> Option<(usize, Token)
             value: String::new(),
             line: context.line,
             position: context.position,
+            file: context.file,
         }
     ))
 }
 
 #[inline]
 fn tokenize_requirement(input: &str, context: &mut TokenContext) -> Option<(usize, Token)> {
-    let delimiter = input.find(|c: char| c.is_whitespace() || c == ',' || c == ':' || c == '#')?;
+    let delimiter = find_delimiter(input, |c: char| c.is_whitespace() || c == ',' || c == ':' || c == '#')?;
     if delimiter == 0 {
         return None;
     }
@@ -271,11 +327,12 @@ fn tokenize_requirement(input: &str, context: &mut TokenContext) -> Option<(usiz
             value: value.to_string(),
             line: context.line,
             position: context.position,
+            file: context.file,
         }
     ))
 }
 
-pub fn tokenize(areas: &Path) -> Result<Vec<Token>, String> {
+fn tokenize_file(areas: &Path, tokens: &mut Vec<Token>, files: &mut Vec<PathBuf>, including: &mut Vec<PathBuf>, tokenized_files: &mut FxHashSet<PathBuf>) -> Result<(), String> {
     let tokenizers = [
         skip_whitespace,
         tokenize_indent,
@@ -291,19 +348,26 @@ pub fn tokenize(areas: &Path) -> Result<Vec<Token>, String> {
         tokenize_quest,             // ~48 occurences
         tokenize_region,            // 20 occurences
         tokenize_definition,        // 2 occurences
+        tokenize_include,
+        tokenize_pathset,
         tokenize_requirement,
     ];
 
     let input = util::read_file(areas, "logic")?;
 
     let length = input.len();
-    let mut tokens = Vec::<Token>::with_capacity(length / 9);
+    tokens.reserve(length / 9);
 
     let mut context = TokenContext {
         line: 1,
         position: 0,
+        file: files.len(),
         indent_stack: smallvec![0],
     };
+    files.push(areas.to_path_buf());
+    let canonical = areas.canonicalize().unwrap_or_else(|_| areas.to_path_buf());
+    tokenized_files.insert(canonical.clone());
+    including.push(canonical);
 
     while context.position < length {
         let mut tokenized = false;
@@ -312,6 +376,21 @@ pub fn tokenize(areas: &Path) -> Result<Vec<Token>, String> {
                 context.position += consumed;
                 match token.name {
                     TokenType::Whitespace => {},
+                    TokenType::Include => {
+                        if context.indent_stack.len() > 1 {
+                            return Err(format!("Includes have to be at the top level, found one at line {} in {}", token.line, areas.display()));
+                        }
+                        // included paths are relative to the including file
+                        let path = areas.parent().unwrap_or_else(|| Path::new("")).join(&token.value);
+                        let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+                        if including.contains(&canonical) {
+                            return Err(format!("{} includes itself through line {} in {}", path.display(), token.line, areas.display()));
+                        }
+                        // files like shared definitions may be included from several places, but only get read once
+                        if !tokenized_files.contains(&canonical) {
+                            tokenize_file(&path, tokens, files, including, tokenized_files)?;
+                        }
+                    },
                     _ => tokens.push(token),
                 }
                 tokenized = true;
@@ -333,7 +412,19 @@ pub fn tokenize(areas: &Path) -> Result<Vec<Token>, String> {
             value: String::new(),
             line: context.line,
             position: context.position,
+            file: context.file,
         });
     }
-    Ok(tokens)
+    including.pop();
+    Ok(())
+}
+
+/// Tokenizes the areas file along with all files it includes
+///
+/// Returns the tokens and the paths their `file` indices refer to
+pub fn tokenize(areas: &Path) -> Result<(Vec<Token>, Vec<PathBuf>), String> {
+    let mut tokens = Vec::new();
+    let mut files = Vec::new();
+    tokenize_file(areas, &mut tokens, &mut files, &mut Vec::new(), &mut FxHashSet::default())?;
+    Ok((tokens, files))
 }
@@ -3,7 +3,10 @@ pub mod world;
 pub mod inventory;
 pub mod generator;
 pub mod headers;
+pub mod seed;
 pub mod util;
+pub mod logic_test;
+pub mod logic_diff;
 
 use std::collections::{HashSet, HashMap};
 
@@ -26,6 +29,8 @@ use log4rs::{
     filter::threshold::ThresholdFilter,
 };
 
+use inventory::Item;
+use seed::{SeedFile, SeedSpawn, SeedPlacement, PlacementSpoiler};
 use world::{
     World,
     graph::{Graph, Node, Pickup},
@@ -35,7 +40,7 @@ use util::{
     Pathset, NodeType, Position,
     settings::{Settings, Spawn},
     uberstate::UberState,
-    constants::{DEFAULT_SPAWN, MOKI_SPAWNS, GORLEK_SPAWNS, RETRIES},
+    constants::{DEFAULT_SPAWN, MOKI_SPAWNS, GORLEK_SPAWNS, RETRIES, SEED_HASH_LENGTH, SEED_HASH_WORDS},
 };
 
 fn pick_spawn<'a, R>(graph: &'a Graph, settings: &Settings, rng: &mut R) -> Result<&'a Node, String>
@@ -66,22 +71,16 @@ where
     return Ok(spawn);
 }
 
-fn write_flags(settings: &Settings, mut flags: Vec<String>) -> String {
+fn write_flags(settings: &Settings, mut flags: Vec<String>) -> Vec<String> {
     for flag in settings.goalmodes.iter().map(|goal| format!("{}", goal)) {
         flags.push(flag);
     }
 
     if matches!(settings.spawn_loc, Spawn::Random | Spawn::FullyRandom) { flags.push(String::from("RandomSpawn")); }
 
-    let flags = flags.join(", ");
+    log::trace!("Derived Flags from Settings: {}", flags.join(", "));
 
-    log::trace!("Derived Flags from Settings: {}", flags);
-
-    if flags.is_empty() {
-        return String::default();
-    }
-
-    format!("Flags: {}\n", flags)
+    flags
 }
 
 #[derive(Debug, Default, Clone)]
@@ -179,21 +178,43 @@ where R: Rng + ?Sized
     Ok((header_block, custom_flags, custom_names))
 }
 
-pub fn generate_seed(graph: &Graph, settings: &Settings, headers: &[String], seed: Option<String>) -> Result<Vec<String>, String> {
-    let config = Settings::write(&settings)?;
-    log::trace!("Generating with Settings: {}", config);
+/// Hashes the seed contents into a few words, so players can verify they have the same seed
+///
+/// This uses FNV-1a, which unlike std's hashers is guaranteed to stay the same across versions and platforms
+pub fn seed_hash(contents: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in contents.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
 
-    let seed = seed.unwrap_or_else(|| {
-        let mut generated_seed = String::new();
-        let numeric = Uniform::from('0'..='9');
-        let mut rng = rand::thread_rng();
+    let word_count = SEED_HASH_WORDS.len() as u64;
+    let mut words = Vec::with_capacity(SEED_HASH_LENGTH);
+    for _ in 0..SEED_HASH_LENGTH {
+        #[allow(clippy::cast_possible_truncation)]
+        words.push(SEED_HASH_WORDS[(hash % word_count) as usize]);
+        hash /= word_count;
+    }
+    words.join(" ")
+}
 
-        for _ in 0..16 {
-            generated_seed.push(numeric.sample(&mut rng));
-        }
+pub fn random_seed() -> String {
+    let mut generated_seed = String::new();
+    let numeric = Uniform::from('0'..='9');
+    let mut rng = rand::thread_rng();
 
-        generated_seed
-    });
+    for _ in 0..16 {
+        generated_seed.push(numeric.sample(&mut rng));
+    }
+
+    generated_seed
+}
+
+pub fn generate_seed(graph: &Graph, settings: &Settings, headers: &[String], seed: Option<String>) -> Result<Vec<SeedFile>, String> {
+    let config = Settings::write(&settings)?;
+    log::trace!("Generating with Settings: {}", config);
+
+    let seed = seed.unwrap_or_else(random_seed);
 
     let mut rng: StdRng = Seeder::from(&seed).make_rng();
     log::trace!("Seeded RNG with {}", seed);
@@ -204,7 +225,7 @@ pub fn generate_seed(graph: &Graph, settings: &Settings, headers: &[String], see
 
     let (header_block, custom_flags, custom_names) = parse_headers(&mut world, headers, settings, &mut rng)?;
 
-    let flag_line = write_flags(settings, custom_flags);
+    let flags = write_flags(settings, custom_flags);
 
     let mut worlds = vec![world];
     for _ in 1..settings.worlds {
@@ -244,52 +265,78 @@ pub fn generate_seed(graph: &Graph, settings: &Settings, headers: &[String], see
         }
     };
 
-    let spawn_lines = spawn_locs.into_iter().map(|spawn_loc| {
+    let spawns = spawn_locs.into_iter().map(|spawn_loc| {
         if spawn_loc.identifier() != DEFAULT_SPAWN {
             let position = spawn_loc.position().ok_or_else(|| format!("Tried to spawn on {} which has no specified coordinates", spawn_loc.identifier()))?;
-            return Ok(format!("Spawn: {}  // {}\n", position, spawn_loc.identifier()));
+            return Ok(Some(SeedSpawn {
+                position: position.clone(),
+                identifier: spawn_loc.identifier().to_string(),
+            }));
         }
-        Ok(String::new())
+        Ok(None)
     }).collect::<Result<Vec<_>, String>>()?;
 
-    let placement_blocks = placements.into_iter().map(|world_placements| {
-        let mut placement_block = String::with_capacity(world_placements.len() * 20);
-
+    let mut hashed = String::new();
+    for (world_placements, spawn) in placements.iter().zip(&spawns) {
+        if let Some(spawn) = spawn {
+            hashed += &format!("Spawn: {}  // {}\n", spawn.position, spawn.identifier);
+        }
         for placement in world_placements {
-            let mut placement_line = format!("{}", placement);
-
-            if settings.spoilers {
-                let location = placement.node.map_or_else(
-                    || placement.uber_state.to_string(),
-                    |node| {
-                        let mut location = node.to_string();
-                        util::add_trailing_spaces(&mut location, 33);
-                        let mut position = format!("({})", node.position().unwrap());
-                        util::add_trailing_spaces(&mut position, 15);
-                        format!("{}  {} {}", location, position, node.zone().unwrap())
-                    }
-                );
-
-                util::add_trailing_spaces(&mut placement_line, 42);
-                let item = custom_names.get(&placement.item.code()).map(|code| code.clone()).unwrap_or_else(|| format!("{}", placement.item));
-                let item = util::with_leading_spaces(&item, 30);
-
-                placement_line += &format!("  // {} from {}", item, location);
-            }
-
-            placement_line.push('\n');
-            placement_block.push_str(&placement_line);
+            hashed += &format!("{}\n", placement);
         }
+    }
+    hashed += &header_block;
+    hashed += &config;
+    let hash = seed_hash(&hashed);
+    log::info!("Seed hash: {}", hash);
+    let hash_placement = SeedPlacement {
+        uber_state: UberState::spawn(),
+        item: Item::Message(format!("Seed hash: {}|f=600", hash)),
+        spoiler: None,
+    };
+
+    let header_lines = header_block.lines().map(str::to_string).collect::<Vec<_>>();
 
-        placement_block
-    }).collect::<Vec<_>>();
+    let seeds = placements.into_iter().zip(spawns).map(|(world_placements, spawn)| {
+        let mut seed_placements = Vec::with_capacity(world_placements.len() + 1);
+        seed_placements.push(hash_placement.clone());
 
-    let seed_line = format!("// Seed: {}", seed);
-    let config_line = format!("// Config: {}", config);
+        for placement in world_placements {
+            let spoiler = PlacementSpoiler {
+                item: custom_names.get(&placement.item.code()).cloned().unwrap_or_else(|| placement.item.to_string()),
+                location: placement.node.map_or_else(|| placement.uber_state.to_string(), |node| node.identifier().to_string()),
+                position: placement.node.and_then(Node::position).cloned(),
+                zone: placement.node.and_then(Node::zone).filter(|zone| !zone.is_empty()).map(str::to_string),
+            };
+
+            seed_placements.push(SeedPlacement {
+                spoiler: Some(spoiler),
+                uber_state: placement.uber_state,
+                item: placement.item,
+            });
+        }
 
-    let seeds = (0..settings.worlds).map(|index| {
-        format!("{}{}\n{}\n{}{}\n{}", flag_line, &spawn_lines[index], &placement_blocks[index], &header_block, &seed_line, &config_line)
-    }).collect::<Vec<_>>();
+        SeedFile {
+            flags: flags.clone(),
+            spawn,
+            placements: seed_placements,
+            headers: header_lines.clone(),
+            seed: Some(seed.clone()),
+            config: Some(settings.clone()),
+        }
+    }).collect();
 
     Ok(seeds)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seed_hashes() {
+        // the hash has to stay the same across versions and platforms for players to compare it
+        assert_eq!(seed_hash("3|0|2|100\n"), "Kwolok Baur Ku Hammer");
+        assert_ne!(seed_hash("3|0|2|100\n"), seed_hash("3|0|2|101\n"));
+    }
+}
@@ -0,0 +1,333 @@
+use std::fmt;
+use std::path::Path;
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::lexer;
+use crate::inventory::Inventory;
+use crate::world::{
+    graph::{Graph, Node},
+    player::Player,
+    requirements::Requirement,
+};
+use crate::util::{
+    Pathsets,
+    settings::Settings,
+    constants::DEFAULT_SPAWN,
+};
+
+// Long paths multiply the itemsets of every connection along them, so only the smallest ones are kept
+const MAX_ITEMSETS: usize = 16;
+
+fn display_itemset(itemset: &Inventory) -> String {
+    let mut items = itemset.inventory.iter().map(|(item, amount)|
+        if *amount == 1 { item.to_string() } else { format!("{}={}", item, amount) }
+    ).collect::<Vec<_>>();
+    items.sort();
+    items.join(", ")
+}
+fn display_itemsets(itemsets: &[Inventory]) -> String {
+    if itemsets.is_empty() { return String::from("unreachable"); }
+    itemsets.iter().map(|itemset| match itemset.inventory.len() {
+        0 => String::from("free"),
+        1 => display_itemset(itemset),
+        _ => format!("({})", display_itemset(itemset)),
+    }).collect::<Vec<_>>().join(" OR ")
+}
+
+fn sort_key(itemset: &Inventory) -> (usize, String) {
+    (itemset.item_count(), display_itemset(itemset))
+}
+
+// Keeps only itemsets that don't contain another one, returns whether the new one was kept
+fn add_itemset(itemsets: &mut Vec<Inventory>, itemset: Inventory) -> bool {
+    if itemsets.iter().any(|existing| itemset.contains(existing)) { return false; }
+    itemsets.retain(|existing| !existing.contains(&itemset));
+
+    let key = sort_key(&itemset);
+    let index = itemsets.iter().position(|existing| sort_key(existing) > key).unwrap_or(itemsets.len());
+    if index >= MAX_ITEMSETS { return false; }
+    itemsets.insert(index, itemset);
+    itemsets.truncate(MAX_ITEMSETS);
+    true
+}
+
+fn itemsets_needed(requirement: &Requirement, player: &Player, states: &[Vec<Inventory>]) -> Vec<Inventory> {
+    let mut itemsets = Vec::new();
+    match requirement {
+        Requirement::State(state) => itemsets = states[*state].clone(),
+        Requirement::And(ands) => {
+            itemsets.push(Inventory::default());
+            for and in ands {
+                let needed = itemsets_needed(and, player, states);
+                let mut combined = Vec::new();
+                for left in &itemsets {
+                    for right in &needed {
+                        add_itemset(&mut combined, left.merge(right));
+                    }
+                }
+                itemsets = combined;
+            }
+        },
+        Requirement::Or(ors) => {
+            for or in ors {
+                for itemset in itemsets_needed(or, player, states) {
+                    add_itemset(&mut itemsets, itemset);
+                }
+            }
+        },
+        _ => {
+            for (needed, orb_cost) in requirement.items_needed(player, &[]) {
+                add_itemset(&mut itemsets, Player::missing_for_orbs(&needed, orb_cost, player.max_orbs()));
+            }
+        },
+    }
+    itemsets
+}
+
+/// Finds the smallest itemsets that reach each node from spawn, indexed like the graph's nodes
+///
+/// Orb costs are compared against the orbs at spawn one requirement at a time, refills along the way aren't taken into account
+pub fn minimal_itemsets(graph: &Graph, player: &Player, spawn: &Node) -> Vec<Vec<Inventory>> {
+    let mut itemsets = vec![Vec::new(); graph.nodes.len()];
+    itemsets[spawn.index()].push(Inventory::default());
+
+    // anchors have to be looked at again once a state they depend on gets reachable in a new way
+    let mut dependents: FxHashMap<usize, Vec<usize>> = FxHashMap::default();
+    for node in &graph.nodes {
+        if let Node::Anchor(anchor) = node {
+            for connection in &anchor.connections {
+                for &state in graph.requirements.contained_states(connection.compiled) {
+                    dependents.entry(state).or_default().push(anchor.index);
+                }
+            }
+        }
+    }
+
+    let mut cache: FxHashMap<usize, Vec<Inventory>> = FxHashMap::default();
+    let mut queued = vec![false; graph.nodes.len()];
+    let mut queue = vec![spawn.index()];
+    queued[spawn.index()] = true;
+
+    while let Some(index) = queue.pop() {
+        queued[index] = false;
+        let anchor = match &graph.nodes[index] {
+            Node::Anchor(anchor) => anchor,
+            _ => continue,
+        };
+        let reaching = itemsets[index].clone();
+
+        for connection in &anchor.connections {
+            let needed = if graph.requirements.contained_states(connection.compiled).is_empty() {
+                cache.entry(connection.compiled).or_insert_with(|| itemsets_needed(&connection.requirement, player, &[])).clone()
+            } else {
+                itemsets_needed(&connection.requirement, player, &itemsets)
+            };
+
+            let mut changed = false;
+            for left in &reaching {
+                for right in &needed {
+                    changed |= add_itemset(&mut itemsets[connection.to], left.merge(right));
+                }
+            }
+            if !changed { continue; }
+
+            let affected = match &graph.nodes[connection.to] {
+                Node::Anchor(_) => vec![connection.to],
+                Node::State(_) => dependents.get(&connection.to).cloned().unwrap_or_default(),
+                _ => Vec::new(),
+            };
+            for affected in affected {
+                if !queued[affected] {
+                    queued[affected] = true;
+                    queue.push(affected);
+                }
+            }
+        }
+    }
+
+    itemsets
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Change {
+    Easier,
+    Harder,
+    Changed,
+}
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Easier => write!(f, "got easier"),
+            Change::Harder => write!(f, "got harder"),
+            Change::Changed => write!(f, "changed"),
+        }
+    }
+}
+
+/// How the items needed for a location changed between two versions of the logic
+#[derive(Debug)]
+pub struct LocationDiff {
+    pub pathset: String,
+    pub location: String,
+    pub change: Change,
+    pub before: Vec<Inventory>,
+    pub after: Vec<Inventory>,
+}
+impl fmt::Display for LocationDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}\n  before: {}\n  after: {}", self.location, self.change, display_itemsets(&self.before), display_itemsets(&self.after))
+    }
+}
+
+// One version of the logic parsed for one pathset
+struct Version {
+    graph: Graph,
+    player: Player,
+    // locations reached with each itemset checked so far
+    reached: FxHashMap<String, FxHashSet<String>>,
+}
+impl Version {
+    fn parse(areas: &Path, locations: &Path, states: &Path, pathset: &str) -> Result<Version, String> {
+        let mut pathsets = Pathsets::from_names(&[pathset.to_string()]);
+        let graph = lexer::parse_logic(areas, locations, states, &pathsets, false)?;
+        pathsets.resolve(&graph.pathsets).map_err(|err| format!("{} in {}", err, areas.display()))?;
+
+        let mut player = Player::default();
+        player.spawn(&Settings { pathsets, ..Settings::default() });
+
+        Ok(Version { graph, player, reached: FxHashMap::default() })
+    }
+
+    fn location_itemsets(&self) -> Result<Vec<(String, Vec<Inventory>)>, String> {
+        let spawn = self.graph.find_spawn(DEFAULT_SPAWN)?;
+        let mut itemsets = minimal_itemsets(&self.graph, &self.player, spawn);
+
+        Ok(self.graph.nodes.iter()
+            .filter(|node| node.can_place())
+            .map(|node| (node.identifier().to_string(), std::mem::take(&mut itemsets[node.index()])))
+            .collect())
+    }
+
+    fn reaches(&mut self, itemset: &Inventory, location: &str) -> Result<bool, String> {
+        let key = display_itemset(itemset);
+        if !self.reached.contains_key(&key) {
+            let mut player = self.player.clone();
+            for (item, amount) in &itemset.inventory {
+                player.inventory.grant(item.clone(), *amount);
+            }
+            let spawn = self.graph.find_spawn(DEFAULT_SPAWN)?;
+            let reached = self.graph.reached_locations(&player, spawn, &FxHashMap::default())?
+                .iter().map(|node| node.identifier().to_string()).collect();
+            self.reached.insert(key.clone(), reached);
+        }
+        Ok(self.reached[&key].contains(location))
+    }
+}
+
+// The itemsets only approximate orb costs and get cut off on long paths, so whether they still work is checked with a reach check on the other version
+fn compare(location: &str, before: &[Inventory], after: &[Inventory], old: &mut Version, new: &mut Version) -> Result<Option<Change>, String> {
+    if before == after { return Ok(None); }
+
+    // everything that worked before still works
+    let mut easier = true;
+    for itemset in before {
+        if !new.reaches(itemset, location)? { easier = false; break; }
+    }
+    let mut harder = true;
+    for itemset in after {
+        if !old.reaches(itemset, location)? { harder = false; break; }
+    }
+
+    Ok(match (easier, harder) {
+        (true, true) => None,
+        (true, false) => Some(Change::Easier),
+        (false, true) => Some(Change::Harder),
+        (false, false) => Some(Change::Changed),
+    })
+}
+
+/// Compares which items each location needs under each pathset between two versions of the logic
+///
+/// Locations that only exist in one of the versions count as unreachable in the other one
+pub fn diff_logic(old: &Path, new: &Path, locations: &Path, states: &Path, pathsets: &[String]) -> Result<Vec<LocationDiff>, String> {
+    let mut diffs = Vec::new();
+
+    for pathset in pathsets {
+        let mut old = Version::parse(old, locations, states, pathset)?;
+        let mut new = Version::parse(new, locations, states, pathset)?;
+        let mut before = old.location_itemsets()?.into_iter().collect::<FxHashMap<_, _>>();
+        let after = new.location_itemsets()?;
+
+        let mut removed = before.keys().filter(|location| !after.iter().any(|(after, _)| after == *location)).cloned().collect::<Vec<_>>();
+        removed.sort();
+        let after = after.into_iter().chain(removed.into_iter().map(|location| (location, Vec::new())));
+
+        for (location, after) in after {
+            let before = before.remove(&location).unwrap_or_default();
+            if let Some(change) = compare(&location, &before, &after, &mut old, &mut new)? {
+                diffs.push(LocationDiff { pathset: pathset.clone(), location, change, before, after });
+            }
+        }
+    }
+
+    Ok(diffs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::inventory::Item;
+    use crate::util::{Skill, Resource};
+
+    #[test]
+    fn logic_diff() {
+        let old = "\
+anchor MarshSpawn.Main:
+  pickup MarshSpawn.RockHC: free
+  pickup MarshSpawn.BridgeEX:
+    moki: DoubleJump
+  pickup MarshSpawn.LongSwimEX:
+    moki: Water
+  pickup MarshSpawn.FirstPickupEX:
+    moki: Bash
+  conn MarshSpawn.BrokenBridge:
+    moki: Bash
+anchor MarshSpawn.BrokenBridge:
+  pickup MarshSpawn.GrappleHC:
+    moki: Grapple
+";
+        let new = &old
+            .replace("moki: DoubleJump", "moki: DoubleJump OR Dash")
+            .replace("moki: Water", "moki: Water, Bash")
+            .replace("    moki: Bash\n  conn", "    moki: Dash\n  conn")
+            .replace("moki: Grapple", "moki: Grapple\n    gorlek: free");
+        let old_path = std::env::temp_dir().join("logic_diff_old.wotw");
+        let new_path = std::env::temp_dir().join("logic_diff_new.wotw");
+        fs::write(&old_path, old).unwrap();
+        fs::write(&new_path, new).unwrap();
+        let diffs = diff_logic(&old_path, &new_path, Path::new("loc_data.csv"), Path::new("state_data.csv"), &[String::from("moki"), String::from("gorlek")]);
+        fs::remove_file(&old_path).unwrap();
+        fs::remove_file(&new_path).unwrap();
+
+        let diffs = diffs.unwrap().into_iter().map(|diff| (diff.pathset, diff.location, diff.change)).collect::<Vec<_>>();
+        let expected = [
+            ("moki", "MarshSpawn.FirstPickupEX", Change::Changed),
+            ("moki", "MarshSpawn.BridgeEX", Change::Easier),
+            ("moki", "MarshSpawn.LongSwimEX", Change::Harder),
+            ("gorlek", "MarshSpawn.FirstPickupEX", Change::Changed),
+            ("gorlek", "MarshSpawn.GrappleHC", Change::Easier),
+            ("gorlek", "MarshSpawn.BridgeEX", Change::Easier),
+            ("gorlek", "MarshSpawn.LongSwimEX", Change::Harder),
+        ].iter().map(|&(pathset, location, change)| (pathset.to_string(), location.to_string(), change)).collect::<Vec<_>>();
+        assert_eq!(diffs, expected);
+
+        let mut itemsets = Vec::new();
+        add_itemset(&mut itemsets, Inventory::from(vec![(Item::Skill(Skill::Bash), 1), (Item::Resource(Resource::Keystone), 2)]));
+        add_itemset(&mut itemsets, Inventory::from(vec![Item::Skill(Skill::Dash), Item::Skill(Skill::Bash)]));
+        add_itemset(&mut itemsets, Inventory::from(Item::Skill(Skill::Dash)));
+        assert_eq!(display_itemsets(&itemsets), "Dash OR (Bash, Keystone=2)");
+        assert_eq!(display_itemsets(&[Inventory::default()]), "free");
+    }
+}
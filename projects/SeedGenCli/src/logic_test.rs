@@ -0,0 +1,242 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rustc_hash::FxHashMap;
+
+use crate::lexer;
+use crate::inventory::Item;
+use crate::world::{
+    graph::Graph,
+    player::Player,
+};
+use crate::util::{
+    self, Pathsets, NodeType,
+    settings::Settings,
+    constants::DEFAULT_SPAWN,
+};
+
+/// One expectation on the logic, read from a logic test file
+///
+/// ```text
+/// # comments work like in the logic
+/// test Bridge with Double Jump:
+///   spawn: MarshSpawn.Main
+///   pathsets: moki
+///   items: DoubleJump, Keystone=2
+///   reachable: MarshSpawn.BridgeEX
+///   unreachable: MarshSpawn.LongSwimEX
+/// ```
+///
+/// Everything except the test line is optional. Item amounts count pickups, so `Health=2` means two health fragments on top of the health at spawn
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogicTest {
+    pub name: String,
+    pub line: usize,
+    pub spawn: String,
+    pub pathsets: Vec<String>,
+    pub items: Vec<(Item, u16)>,
+    pub reachable: Vec<String>,
+    pub unreachable: Vec<String>,
+}
+
+fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    value.split(',').map(str::trim).filter(|entry| !entry.is_empty())
+}
+
+fn parse_item(item: &str, line: usize) -> Result<(Item, u16), String> {
+    let (name, amount) = match item.split_once('=') {
+        Some((name, amount)) => {
+            let amount = amount.trim().parse().map_err(|_| format!("Invalid item amount in {} at line {}", item, line))?;
+            (name.trim(), amount)
+        },
+        None => (item, 1),
+    };
+    let item = Item::from_name(name).ok_or_else(|| format!("Unknown item {} at line {}", name, line))?;
+    Ok((item, amount))
+}
+
+/// Parses the tests in a logic test file
+pub fn parse(input: &str) -> Result<Vec<LogicTest>, String> {
+    let mut tests: Vec<LogicTest> = Vec::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.split('#').next().unwrap();
+        let indented = line.starts_with(char::is_whitespace);
+        let line = line.trim();
+        if line.is_empty() { continue; }
+
+        if !indented {
+            let name = line.strip_prefix("test ").and_then(|name| name.strip_suffix(':'))
+                .ok_or_else(|| format!("Expected a test like \"test name:\" at line {}", line_number))?;
+            tests.push(LogicTest {
+                name: name.trim().to_string(),
+                line: line_number,
+                spawn: DEFAULT_SPAWN.to_string(),
+                pathsets: Vec::new(),
+                items: Vec::new(),
+                reachable: Vec::new(),
+                unreachable: Vec::new(),
+            });
+            continue;
+        }
+
+        let test = tests.last_mut().ok_or_else(|| format!("Indented line outside of a test at line {}", line_number))?;
+        let (key, value) = line.split_once(':').ok_or_else(|| format!("Expected a property like \"items: Bash\" at line {}", line_number))?;
+        let value = value.trim();
+        match key.trim() {
+            "spawn" => test.spawn = value.to_string(),
+            "pathsets" => test.pathsets.extend(split_list(value).map(str::to_string)),
+            "items" => {
+                for item in split_list(value) {
+                    test.items.push(parse_item(item, line_number)?);
+                }
+            },
+            "reachable" => test.reachable.extend(split_list(value).map(str::to_string)),
+            "unreachable" => test.unreachable.extend(split_list(value).map(str::to_string)),
+            other => return Err(format!("Unknown property {} at line {}", other, line_number)),
+        }
+    }
+
+    Ok(tests)
+}
+
+impl LogicTest {
+    /// Returns the expectations that don't hold on the graph
+    pub fn run(&self, graph: &Graph) -> Result<Vec<String>, String> {
+        let mut pathsets = Pathsets::from_names(&self.pathsets);
+        pathsets.resolve(&graph.pathsets)?;
+
+        let mut player = Player::default();
+        player.spawn(&Settings { pathsets, ..Settings::default() });
+        for (item, amount) in &self.items {
+            player.inventory.grant(item.clone(), *amount);
+        }
+
+        let spawn = graph.find_spawn(&self.spawn)?;
+        let reached = graph.reached_locations(&player, spawn, &FxHashMap::default())?;
+
+        let mut failures = Vec::new();
+        for (identifiers, expected) in [(&self.reachable, true), (&self.unreachable, false)] {
+            for identifier in identifiers {
+                let node = graph.nodes.iter().find(|node| node.identifier() == identifier).ok_or_else(|| format!("{} not found", identifier))?;
+                if node.node_type() == NodeType::Anchor {
+                    return Err(format!("{} is an anchor, expected a pickup, state or quest", identifier));
+                }
+
+                let is_reached = reached.iter().any(|reached| reached.index() == node.index());
+                if is_reached != expected {
+                    failures.push(format!("expected {} to be {}", identifier, if expected { "reachable" } else { "unreachable" }));
+                }
+            }
+        }
+
+        Ok(failures)
+    }
+}
+
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    if path.is_dir() {
+        let mut entries = fs::read_dir(path).map_err(|err| format!("Failed to read directory {}: {}", path.display(), err))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| format!("Failed to read directory {}: {}", path.display(), err))?;
+        entries.sort();
+
+        for entry in entries {
+            if entry.is_dir() || matches!(entry.extension(), Some(extension) if extension == "wotwtest") {
+                collect_files(&entry, files)?;
+            }
+        }
+    } else {
+        files.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+/// Summary of a test run over one or more logic test files
+#[derive(Debug, Default)]
+pub struct TestReport {
+    pub passed: usize,
+    pub failed: usize,
+    pub output: String,
+}
+
+/// Runs the tests in the given files and folders, searching folders for `.wotwtest` files
+pub fn run_tests(paths: &[PathBuf], areas: &Path, locations: &Path, states: &Path) -> Result<TestReport, String> {
+    let mut files = Vec::new();
+    for path in paths {
+        collect_files(path, &mut files)?;
+    }
+
+    // the pathsets affect how the logic gets parsed, so tests with the same pathsets share a graph
+    let mut graphs: Vec<(Pathsets, Graph)> = Vec::new();
+    let mut report = TestReport::default();
+
+    for file in files {
+        let contents = util::read_file(&file, "logic_tests")?;
+        let tests = parse(&contents).map_err(|err| format!("Error parsing {}: {}", file.display(), err))?;
+
+        for test in tests {
+            let pathsets = Pathsets::from_names(&test.pathsets);
+            let graph_index = match graphs.iter().position(|(existing, _)| existing == &pathsets) {
+                Some(index) => index,
+                None => {
+                    let graph = lexer::parse_logic(areas, locations, states, &pathsets, false)?;
+                    graphs.push((pathsets, graph));
+                    graphs.len() - 1
+                },
+            };
+
+            let result = test.run(&graphs[graph_index].1).and_then(|failures|
+                if failures.is_empty() { Ok(()) } else { Err(failures.join(", ")) }
+            );
+            match result {
+                Ok(()) => {
+                    report.passed += 1;
+                    writeln!(report.output, "ok      {}:{} {}", file.display(), test.line, test.name).unwrap();
+                },
+                Err(err) => {
+                    report.failed += 1;
+                    writeln!(report.output, "FAILED  {}:{} {}: {}", file.display(), test.line, test.name, err).unwrap();
+                },
+            }
+        }
+    }
+
+    write!(report.output, "\n{} passed, {} failed", report.passed, report.failed).unwrap();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::Skill;
+
+    #[test]
+    fn logic_tests() {
+        let input = "
+# sample
+test Nothing:
+  reachable: MarshSpawn.RockHC
+  unreachable: MarshSpawn.BridgeEX, MarshSpawn.RockHC
+test Double Jump:  # comment
+  pathsets: moki
+  items: DoubleJump, Keystone=2
+  reachable: MarshSpawn.BridgeEX
+";
+        let tests = parse(input).unwrap();
+        assert_eq!(tests.len(), 2);
+        assert_eq!(tests[1].name, "Double Jump");
+        assert_eq!(tests[1].line, 6);
+        assert_eq!(tests[1].items, vec![(Item::Skill(Skill::DoubleJump), 1), (Item::Resource(util::Resource::Keystone), 2)]);
+        assert!(parse("  items: Bash").is_err());
+        assert!(parse("test a:\n  items: NotAnItem").is_err());
+
+        let graph = lexer::parse_logic(Path::new("areas.wotw"), Path::new("loc_data.csv"), Path::new("state_data.csv"), &Pathsets::default(), false).unwrap();
+        assert_eq!(tests[0].run(&graph).unwrap(), vec![String::from("expected MarshSpawn.RockHC to be unreachable")]);
+        assert!(tests[1].run(&graph).unwrap().is_empty());
+        assert!(parse("test a:\n  reachable: MarshSpawn.Main").unwrap()[0].run(&graph).is_err());
+    }
+}
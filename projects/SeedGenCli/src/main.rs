@@ -1,8 +1,10 @@
 mod presets;
+mod mystery;
 
 use std::{
     fs,
-    path::PathBuf,
+    fmt::Write,
+    path::{Path, PathBuf},
     convert::TryFrom,
     io::{self, Read},
     time::Instant,
@@ -11,16 +13,18 @@ use std::{
 
 use structopt::StructOpt;
 use bugsalot::debugger;
+use rand::rngs::StdRng;
+use rand_seeder::Seeder;
 
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
 use log::LevelFilter;
 
-use seedgen::{self, lexer, inventory, world, headers, util};
+use seedgen::{self, lexer, inventory, world, headers, logic_test, logic_diff, seed::{self, SeedFile}, util};
 
 use inventory::Item;
 use world::World;
 use util::{
-    Pathsets, Pathset, GoalMode, Resource, Skill, Teleporter, Shard,
+    Pathsets, GoalMode, Resource, Skill, Teleporter, Shard,
     settings::{Settings, Spawn},
     uberstate::{UberState, UberValue},
 };
@@ -49,6 +53,16 @@ enum SeedGenCommand {
         /// write the seed to stdout instead of a file
         #[structopt(long)]
         tostdout: bool,
+        /// wotwr, json or both
+        ///
+        /// json seeds are meant for other tools, the randomizer itself reads wotwr seeds
+        #[structopt(long, default_value = "wotwr")]
+        format: SeedFormat,
+        /// in race mode, lock the separate spoiler file with this passphrase
+        ///
+        /// use spoiler unlock with the same passphrase to read it after the race
+        #[structopt(long)]
+        passphrase: Option<String>,
     },
     /// Play the most recent generated seed
     Play,
@@ -56,12 +70,36 @@ enum SeedGenCommand {
     Preset {
         #[structopt(flatten)]
         args: PresetArgs,
+        #[structopt(subcommand)]
+        subcommand: Option<PresetCommand>,
     },
     /// Check which locations are in logic
     ReachCheck {
         #[structopt(flatten)]
         args: ReachCheckArgs,
     },
+    /// Work with seed spoilers
+    Spoiler {
+        #[structopt(subcommand)]
+        subcommand: SpoilerCommand,
+    },
+    /// Compare two seeds
+    Diff {
+        /// the seed to compare against
+        #[structopt(parse(from_os_str))]
+        old: PathBuf,
+        /// the seed to compare
+        #[structopt(parse(from_os_str))]
+        new: PathBuf,
+        /// the input file representing pickup locations
+        #[structopt(parse(from_os_str), default_value = "loc_data.csv", short, long)]
+        locations: PathBuf,
+    },
+    /// Inspect the logic files
+    Logic {
+        #[structopt(subcommand)]
+        subcommand: LogicCommand,
+    },
     /// Inspect the available headers
     Headers {
         /// headers to look at in detail
@@ -82,6 +120,16 @@ struct SeedArgs {
     /// presets later in the list override earlier ones, and flags from the command override any preset
     #[structopt(short, long)]
     preset: Vec<String>,
+    /// derive the settings from a settings code
+    ///
+    /// settings codes are printed when generating a seed or creating a preset. They get applied after any presets
+    #[structopt(long)]
+    settings_code: Option<String>,
+    /// roll additional settings from a file of weights, seeded the same way as the seed
+    ///
+    /// see the mystery_weights.json preset for the format
+    #[structopt(parse(from_os_str), long)]
+    mystery: Option<PathBuf>,
     /// seed the rng; without this flag it will be seeded from the filename instead
     #[structopt(long)]
     seed: Option<String>,
@@ -109,7 +157,7 @@ struct PresetArgs {
     ///
     /// later you can run seed -p <preset-name> to use this preset
     #[structopt(parse(from_os_str))]
-    name: PathBuf,
+    name: Option<PathBuf>,
     #[structopt(flatten)]
     settings: SeedSettings,
 }
@@ -144,6 +192,7 @@ struct SeedSettings {
     /// which pathsets to use
     /// 
     /// pathsets are moki, gorlek, glitch, unsafe, sjump, swordsjump, hammersjump, shurikenbreak, sentryburn, removekillplane
+    /// and any pathsets declared in the logic, see logic pathsets
     #[structopt(short, long)]
     logic: Vec<String>,
     /// paths to headers stored in files which will be added to the seed
@@ -165,20 +214,210 @@ struct ReachCheckArgs {
     /// the input file representing state namings
     #[structopt(parse(from_os_str), default_value = "state_data.csv", short, long)]
     uber_states: PathBuf,
+    /// an uber state dump from the game to infer the player's items from, instead of passing them
+    ///
+    /// locations the dump shows as collected are left out of the result
+    #[structopt(parse(from_os_str), long)]
+    dump: Option<PathBuf>,
+    /// also list which items would unlock more locations, and how many
+    #[structopt(long)]
+    progressions: bool,
+    /// output the reached locations, states and quests as json, along with the player's max health and energy
+    #[structopt(long)]
+    json: bool,
     /// player health (one orb is 10 health)
-    health: u16,
+    #[structopt(required_unless = "dump")]
+    health: Option<u16>,
     /// player energy (one orb is 1 energy)
-    energy: f32,
+    #[structopt(required_unless = "dump")]
+    energy: Option<f32>,
     /// player keystones
-    keystones: u16,
+    #[structopt(required_unless = "dump")]
+    keystones: Option<u16>,
     /// player ore
-    ore: u16,
+    #[structopt(required_unless = "dump")]
+    ore: Option<u16>,
     /// player spirit light
-    spirit_light: u32,
-    /// any additional player items in the format s:<skill id>, t:<teleporter id>, sh:<shard id>, w:<world event id> or u:<ubergroup>,<uberid>
+    #[structopt(required_unless = "dump")]
+    spirit_light: Option<u32>,
+    /// any additional player items by name, like Launch, Overflow or MarshTP
+    ///
+    /// the older format s:<skill id>, t:<teleporter id>, sh:<shard id>, w:<world event id> or u:<ubergroup>,<uberid> works as well
     items: Vec<String>,
 }
 
+#[derive(StructOpt)]
+enum PresetCommand {
+    /// Print the fully resolved settings of one or more presets
+    ///
+    /// presets get combined the same way as when passing them to seed -p
+    Show {
+        /// names of built-in presets or preset files
+        #[structopt(required = true)]
+        presets: Vec<String>,
+    },
+}
+
+#[derive(StructOpt)]
+enum LogicCommand {
+    /// List the pathsets declared in the logic, which can be passed to seed --logic
+    Pathsets {
+        /// the input file representing the logic
+        #[structopt(parse(from_os_str), default_value = "areas.wotw", long)]
+        areas: PathBuf,
+        /// the input file representing pickup locations
+        #[structopt(parse(from_os_str), default_value = "loc_data.csv", long)]
+        locations: PathBuf,
+        /// the input file representing state namings
+        #[structopt(parse(from_os_str), default_value = "state_data.csv", long)]
+        uber_states: PathBuf,
+    },
+    /// Check the logic against logic test files
+    ///
+    /// Each test lists items, pathsets and a spawn and which locations should or shouldn't be reachable with them
+    Test {
+        /// the test files, or folders to search for .wotwtest files
+        #[structopt(parse(from_os_str), default_value = "logic_tests")]
+        files: Vec<PathBuf>,
+        /// the input file representing the logic
+        #[structopt(parse(from_os_str), default_value = "areas.wotw", long)]
+        areas: PathBuf,
+        /// the input file representing pickup locations
+        #[structopt(parse(from_os_str), default_value = "loc_data.csv", long)]
+        locations: PathBuf,
+        /// the input file representing state namings
+        #[structopt(parse(from_os_str), default_value = "state_data.csv", long)]
+        uber_states: PathBuf,
+    },
+    /// Rewrite the logic and the files it includes in the canonical style
+    ///
+    /// This normalizes indentation and spacing and orders pathset groups from moki to unsafe, comments are kept
+    Fmt {
+        /// the input file representing the logic
+        #[structopt(parse(from_os_str), default_value = "areas.wotw", long)]
+        areas: PathBuf,
+        /// only list the files that aren't formatted and fail if there are any, without changing them
+        #[structopt(long)]
+        check: bool,
+    },
+    /// Compare two versions of the logic by what each location needs from spawn
+    ///
+    /// Lists the locations that got easier, harder or changed otherwise under each pathset
+    Diff {
+        /// the logic before the change
+        #[structopt(parse(from_os_str))]
+        old: PathBuf,
+        /// the logic after the change
+        #[structopt(parse(from_os_str))]
+        new: PathBuf,
+        /// the pathsets to compare under, one at a time
+        #[structopt(short, long, default_value = "moki,gorlek,unsafe", use_delimiter = true)]
+        logic: Vec<String>,
+        /// the input file representing pickup locations
+        #[structopt(parse(from_os_str), default_value = "loc_data.csv", long)]
+        locations: PathBuf,
+        /// the input file representing state namings
+        #[structopt(parse(from_os_str), default_value = "state_data.csv", long)]
+        uber_states: PathBuf,
+    },
+}
+
+#[derive(StructOpt)]
+enum SpoilerCommand {
+    /// Unlock a spoiler that was locked with a passphrase
+    Unlock {
+        /// the locked spoiler file
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+        /// the passphrase used when generating the seed
+        #[structopt(long)]
+        passphrase: String,
+    },
+    /// Render a seed's spoiler as a readable document, with placements grouped by zone and sorted by sphere
+    Render {
+        /// the seed (or unlocked spoiler) to render
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+        /// html or markdown
+        #[structopt(short, long, default_value = "html")]
+        format: SpoilerFormat,
+        /// the input file representing the logic
+        #[structopt(parse(from_os_str), default_value = "areas.wotw", long)]
+        areas: PathBuf,
+        /// the input file representing pickup locations
+        #[structopt(parse(from_os_str), default_value = "loc_data.csv", long)]
+        locations: PathBuf,
+        /// the input file representing state namings
+        #[structopt(parse(from_os_str), default_value = "state_data.csv", long)]
+        uber_states: PathBuf,
+    },
+    /// Draw an svg map of all locations, colored by the items placed on them
+    Map {
+        /// the seed (or unlocked spoiler) to draw
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+        /// also draw the connections between anchors
+        #[structopt(short, long)]
+        connections: bool,
+        /// the input file representing the logic
+        #[structopt(parse(from_os_str), default_value = "areas.wotw", long)]
+        areas: PathBuf,
+        /// the input file representing pickup locations
+        #[structopt(parse(from_os_str), default_value = "loc_data.csv", long)]
+        locations: PathBuf,
+        /// the input file representing state namings
+        #[structopt(parse(from_os_str), default_value = "state_data.csv", long)]
+        uber_states: PathBuf,
+    },
+}
+
+enum SeedFormat {
+    Wotwr,
+    Json,
+    Both,
+}
+impl std::str::FromStr for SeedFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<SeedFormat, String> {
+        match format {
+            "wotwr" => Ok(SeedFormat::Wotwr),
+            "json" => Ok(SeedFormat::Json),
+            "both" => Ok(SeedFormat::Both),
+            other => Err(format!("Unknown seed format {}, expected wotwr, json or both", other)),
+        }
+    }
+}
+impl SeedFormat {
+    /// Writes the seed in each of the format's file types, paired with their extension
+    fn write(&self, seed_file: &SeedFile, spoilers: bool) -> Result<Vec<(String, &'static str)>, String> {
+        let mut files = Vec::new();
+        if matches!(self, SeedFormat::Wotwr | SeedFormat::Both) {
+            files.push((seed_file.write(spoilers)?, "wotwr"));
+        }
+        if matches!(self, SeedFormat::Json | SeedFormat::Both) {
+            files.push((seed_file.write_json(spoilers)?, "json"));
+        }
+        Ok(files)
+    }
+}
+
+enum SpoilerFormat {
+    Html,
+    Markdown,
+}
+impl std::str::FromStr for SpoilerFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<SpoilerFormat, String> {
+        match format {
+            "html" => Ok(SpoilerFormat::Html),
+            "md" | "markdown" => Ok(SpoilerFormat::Markdown),
+            other => Err(format!("Unknown spoiler format {}, expected html or markdown", other)),
+        }
+    }
+}
+
 #[derive(StructOpt)]
 enum HeaderCommand {
     /// Check header compability
@@ -208,30 +447,6 @@ fn read_header() -> String {
     output
 }
 
-fn parse_pathsets(names: &[String]) -> Pathsets {
-    let mut pathsets = Pathsets::default();
-
-    for pathset in names {
-        match &pathset[..] {
-            "mo" | "moki" => {},
-            "go" | "gorlek" => pathsets.add(Pathset::Gorlek),
-            "un" | "unsafe" => pathsets.add(Pathset::Unsafe),
-            "gl" | "glitch" => pathsets.add_glitches(),
-            "shurikenbreak" => pathsets.add(Pathset::ShurikenBreak),
-            "sjump" | "sentryjump" => {
-                pathsets.add(Pathset::SwordSentryJump);
-                pathsets.add(Pathset::HammerSentryJump);
-            },
-            "swordsjump" | "swordsentryjump" => pathsets.add(Pathset::SwordSentryJump),
-            "hammersjump" | "hammersentryjump" => pathsets.add(Pathset::HammerSentryJump),
-            "sentryburn" => pathsets.add(Pathset::SentryBurn),
-            "removekillplane" => pathsets.add(Pathset::RemoveKillPlane),
-            other => log::warn!("Unknown pathset {}", other),
-        }
-    }
-
-    pathsets
-}
 fn parse_goalmodes(names: &[String]) -> FxHashSet<GoalMode> {
     let mut goalmodes = FxHashSet::default();
 
@@ -267,7 +482,7 @@ fn parse_settings(settings: SeedSettings) -> Settings {
         header_paths,
     } = settings;
 
-    let pathsets = parse_pathsets(&logic);
+    let pathsets = Pathsets::from_names(&logic);
     let goalmodes = parse_goalmodes(&goals);
     let spawn = parse_spawn(spawn);
 
@@ -285,7 +500,9 @@ fn parse_settings(settings: SeedSettings) -> Settings {
     }
 }
 
-fn generate_seeds(mut args: SeedArgs) -> Result<Vec<String>, String> {
+type SeedFiles = Vec<(String, &'static str)>;
+
+fn generate_seeds(mut args: SeedArgs, format: &SeedFormat) -> Result<(SeedFiles, SeedFiles), String> {
     let now = Instant::now();
 
     let seed = args.seed.as_ref().map_or_else(
@@ -295,24 +512,44 @@ fn generate_seeds(mut args: SeedArgs) -> Result<Vec<String>, String> {
 
     let mut settings = Settings::default();
     for preset in args.preset {
-        let preset_settings = match &preset[..] {
-            "moki" => presets::moki(),
-            "gorlek" => presets::gorlek(),
-            "gorlekg" | "gorlek_glitch" => presets::gorlek_glitch(),
-            _ => {
-                let mut preset = PathBuf::from(preset);
-                preset.set_extension("json");
-                Settings::from_preset(&preset)?
-            }
-        };
-
-        settings.merge(preset_settings);
+        settings.merge(presets::read(&preset)?);
+    }
+    if let Some(code) = &args.settings_code {
+        settings.merge(Settings::from_code(code)?);
     }
 
     settings.merge(parse_settings(args.settings));
+
+    let mut seed = seed;
+    let mut hidden_settings = false;
+    if let Some(weights) = &args.mystery {
+        let weights = mystery::Weights::from_file(weights)?;
+        let seed = seed.get_or_insert_with(seedgen::random_seed);
+        // the generator seeds its own rng from the seed, so derive a separate one for the rolls
+        let mut rng: StdRng = Seeder::from(&format!("{}_mystery", seed)).make_rng();
+
+        let rolled = weights.roll(&mut rng)?;
+        let rolled_spawn = rolled.spawn_loc.clone();
+        settings.merge(rolled);
+        // merge skips a spawn that equals the default, but a rolled spawn has to override the one set before
+        if !weights.spawn.is_empty() {
+            settings.spawn_loc = rolled_spawn;
+        }
+        hidden_settings = weights.hidden;
+    }
+
     settings.version = Some(env!("CARGO_PKG_VERSION").to_string());
 
+    if hidden_settings {
+        let filename = sidecar_path(&args.filename, "_settings", "json");
+        let file = util::create_new_file(&filename, &Settings::write(&settings)?, "seeds", true)?;
+        log::info!("Rolled mystery settings, they are hidden in {}", file.display());
+    } else {
+        log::info!("Settings code: {}", settings.to_code()?);
+    }
+
     let graph = lexer::parse_logic(&args.areas, &args.locations, &args.uber_states, &settings.pathsets, !args.trust)?;
+    settings.pathsets.resolve(&graph.pathsets)?;
     log::info!("Parsed logic in {:?}", now.elapsed());
 
     let header = read_header();
@@ -320,26 +557,48 @@ fn generate_seeds(mut args: SeedArgs) -> Result<Vec<String>, String> {
         args.headers.push(header)
     }
 
-    let seeds = seedgen::generate_seed(&graph, &settings, &args.headers, seed).map_err(|err| format!("Error generating seed: {}", err))?;
+    let mut seed_files = seedgen::generate_seed(&graph, &settings, &args.headers, seed).map_err(|err| format!("Error generating seed: {}", err))?;
+
+    let spoilers = if settings.spoilers {
+        Vec::new()
+    } else {
+        seed_files.iter().map(|seed_file| format.write(seed_file, true)).collect::<Result<Vec<_>, _>>()?.concat()
+    };
+    if hidden_settings {
+        for seed_file in &mut seed_files {
+            seed_file.config = None;
+        }
+    }
+    let seeds = seed_files.iter().map(|seed_file| format.write(seed_file, settings.spoilers)).collect::<Result<Vec<_>, _>>()?.concat();
     if settings.worlds == 1 {
         log::info!("Generated seed in {:?}", now.elapsed());
     } else {
         log::info!("Generated {} worlds in {:?}", settings.worlds, now.elapsed());
     }
 
-    Ok(seeds)
+    Ok((seeds, spoilers))
+}
+
+/// The path of a file that belongs to the seed, such as its spoiler
+fn sidecar_path(filename: &Option<PathBuf>, suffix: &str, extension: &str) -> PathBuf {
+    let mut filename = filename.clone().unwrap_or_else(|| PathBuf::from("seed"));
+    let mut stem = filename.file_stem().unwrap_or_default().to_os_string();
+    stem.push(suffix);
+    filename.set_file_name(stem);
+    filename.set_extension(extension);
+    filename
 }
 
-fn write_seeds_to_files(seeds: Vec<String>, filename: Option<PathBuf>) -> Result<(), String> {
+fn write_seeds_to_files(seeds: SeedFiles, filename: Option<PathBuf>) -> Result<(), String> {
     let mut filename = filename.unwrap_or_else(|| PathBuf::from("seed"));
-    filename.set_extension("wotwr");
 
     let mut first = true;
-    for seed in seeds {
+    for (seed, extension) in seeds {
+        filename.set_extension(extension);
         let file = util::create_new_file(&filename, &seed, "seeds", true)?;
         log::info!("Wrote seed to {}", file.display());
 
-        if first {
+        if first && extension == "wotwr" {
             fs::write(".currentseedpath", file.to_string_lossy().into_owned()).unwrap_or_else(|err| log::warn!("Unable to write .currentseedpath: {}", err));
             first = false;
         }
@@ -348,7 +607,136 @@ fn write_seeds_to_files(seeds: Vec<String>, filename: Option<PathBuf>) -> Result
     Ok(())
 }
 
-fn write_seeds_to_stdout(seeds: Vec<String>) {
+fn write_spoilers_to_files(spoilers: SeedFiles, filename: &Option<PathBuf>, passphrase: Option<String>) -> Result<(), String> {
+    if spoilers.is_empty() && passphrase.is_some() {
+        log::warn!("Ignoring the passphrase since only race seeds have a separate spoiler");
+    }
+
+    for (spoiler, extension) in spoilers {
+        let (spoiler, filename) = match &passphrase {
+            // keep locked json spoilers apart from the wotwr ones, unlocking restores the extension
+            Some(passphrase) => (util::spoiler::lock(&spoiler, passphrase)?, sidecar_path(filename, if extension == "json" { "_spoiler_json" } else { "_spoiler" }, "locked")),
+            None => (spoiler, sidecar_path(filename, "_spoiler", extension)),
+        };
+        let file = util::create_new_file(&filename, &spoiler, "seeds", true)?;
+        log::info!("Wrote spoiler to {}", file.display());
+    }
+
+    Ok(())
+}
+
+fn unlock_spoiler(mut file: PathBuf, passphrase: &str) -> Result<(), String> {
+    if file.extension().is_none() {
+        file.set_extension("locked");
+    }
+    let locked = util::read_file(&file, "seeds")?;
+    let spoiler = util::spoiler::unlock(&locked, passphrase)?;
+
+    if spoiler.starts_with('{') {
+        let stem = file.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        file.set_file_name(stem.strip_suffix("_json").unwrap_or(&stem));
+        file.set_extension("json");
+    } else {
+        file.set_extension("wotwr");
+    }
+    let file = util::create_new_file(&file, &spoiler, "seeds", true)?;
+    log::info!("Unlocked spoiler to {}", file.display());
+
+    Ok(())
+}
+
+fn render_spoiler(file: PathBuf, format: &SpoilerFormat, areas: &Path, locations: &Path, uber_states: &Path) -> Result<(), String> {
+    let seed_file = read_seed_file(file.clone())?;
+    let settings = seed_settings(&seed_file, &file)?;
+
+    let graph = lexer::parse_logic(areas, locations, uber_states, &settings.pathsets, false)?;
+    let rows = seed::render::spoiler_rows(&seed_file, &graph, &settings)?;
+
+    let (contents, extension) = match format {
+        SpoilerFormat::Html => (seed::render::render_html(&seed_file, &rows), "html"),
+        SpoilerFormat::Markdown => (seed::render::render_markdown(&seed_file, &rows), "md"),
+    };
+    let suffix = if file.file_stem().map_or(false, |stem| stem.to_string_lossy().ends_with("_spoiler")) { "" } else { "_spoiler" };
+    let file = util::create_new_file(&sidecar_path(&Some(file), suffix, extension), &contents, "seeds", true)?;
+    log::info!("Wrote rendered spoiler to {}", file.display());
+
+    Ok(())
+}
+
+fn draw_map(file: PathBuf, connections: bool, areas: &Path, locations: &Path, uber_states: &Path) -> Result<(), String> {
+    let seed_file = read_seed_file(file.clone())?;
+    let settings = seed_settings(&seed_file, &file)?;
+
+    let graph = lexer::parse_logic(areas, locations, uber_states, &settings.pathsets, false)?;
+    let svg = seed::map::render_svg(&seed_file, &graph, connections)?;
+
+    let file = util::create_new_file(&sidecar_path(&Some(file), "_map", "svg"), &svg, "seeds", true)?;
+    log::info!("Wrote map to {}", file.display());
+
+    Ok(())
+}
+
+fn list_pathsets(areas: &Path, locations: &Path, uber_states: &Path) -> Result<String, String> {
+    let graph = lexer::parse_logic(areas, locations, uber_states, &Pathsets::default(), false)?;
+    if graph.pathsets.is_empty() {
+        return Ok(String::from("The logic doesn't declare any pathsets"));
+    }
+
+    let mut output = String::new();
+    for pathset in &graph.pathsets {
+        write!(output, "{}", pathset.name).unwrap();
+        if let Some(parent) = &pathset.parent {
+            write!(output, " (includes {})", parent).unwrap();
+        }
+        if !pathset.description.is_empty() {
+            write!(output, ": {}", pathset.description).unwrap();
+        }
+        output.push('\n');
+    }
+    output.pop();
+    Ok(output)
+}
+
+fn format_logic(areas: &Path, check: bool) -> Result<(), String> {
+    let changed = lexer::format_logic(areas)?;
+
+    if check {
+        if changed.is_empty() { return Ok(()); }
+        for (file, _) in &changed {
+            println!("{} is not formatted", file.display());
+        }
+        return Err(format!("{} file{} would be reformatted", changed.len(), if changed.len() == 1 { "" } else { "s" }));
+    }
+
+    for (file, formatted) in &changed {
+        fs::write(file, formatted).map_err(|err| format!("Failed to write {}: {}", file.display(), err))?;
+        log::info!("Formatted {}", file.display());
+    }
+    Ok(())
+}
+
+fn diff_logic(old: &Path, new: &Path, locations: &Path, uber_states: &Path, pathsets: &[String]) -> Result<String, String> {
+    let diffs = logic_diff::diff_logic(old, new, locations, uber_states, pathsets)?;
+
+    let mut output = String::new();
+    for pathset in pathsets {
+        let changes = diffs.iter().filter(|diff| &diff.pathset == pathset).collect::<Vec<_>>();
+        if changes.is_empty() {
+            writeln!(output, "{}: no changes", pathset).unwrap();
+            continue;
+        }
+
+        writeln!(output, "{}: {} location{} changed", pathset, changes.len(), if changes.len() == 1 { "" } else { "s" }).unwrap();
+        for change in changes {
+            writeln!(output, "  {}", change.to_string().replace('\n', "\n  ")).unwrap();
+        }
+    }
+    output.pop();
+    Ok(output)
+}
+
+fn write_seeds_to_stdout(seeds: SeedFiles) {
+    let seeds = seeds.into_iter().map(|(seed, _)| seed).collect::<Vec<_>>();
     println!("{}", seeds.join("\n======= END SEED ========\n"));
 }
 
@@ -362,47 +750,84 @@ fn play_last_seed() -> Result<(), String> {
     Ok(())
 }
 
-fn create_preset(mut args: PresetArgs) -> Result<(), String> {
+fn create_preset(args: PresetArgs) -> Result<(), String> {
+    let mut name = args.name.ok_or_else(|| String::from("Please provide a name for the preset"))?;
     let settings = parse_settings(args.settings);
+    let code = settings.to_code()?;
     let settings = Settings::write(&settings)?;
 
-    args.name.set_extension("json");
+    name.set_extension("json");
 
-    let path = util::create_new_file(&args.name, &settings, "presets", false)?;
+    let path = util::create_new_file(&name, &settings, "presets", false)?;
     log::info!("Created preset {}", path.display());
+    log::info!("Settings code: {}", code);
 
     Ok(())
 }
 
-fn reach_check(mut args: ReachCheckArgs) -> Result<String, String> {
-    args.seed_file.set_extension("wotwr");
-    let contents = util::read_file(&args.seed_file, "seeds")?;
+fn show_presets(presets: Vec<String>) -> Result<String, String> {
+    let mut settings = Settings::default();
+    for preset in presets {
+        settings.merge(presets::read(&preset)?);
+    }
 
-    let settings = Settings::from_seed(&contents)?;
-    let graph = &lexer::parse_logic(&args.areas, &args.locations, &args.uber_states, &settings.pathsets, false)?;
-    let mut world = World::new(graph);
+    log::info!("Settings code: {}", settings.to_code()?);
+    serde_json::to_string_pretty(&settings).map_err(|err| format!("Invalid Settings: {}", err))
+}
+
+fn read_seed_file(mut path: PathBuf) -> Result<SeedFile, String> {
+    if path.extension().is_none() {
+        path.set_extension("wotwr");
+    }
+    let contents = util::read_file(&path, "seeds")?;
+    SeedFile::parse(&contents).map_err(|err| format!("Failed to read seed {}: {}", path.display(), err))
+}
+
+/// The settings the seed was generated with, falling back to the settings file written next to it if they were hidden
+fn seed_settings(seed_file: &SeedFile, path: &Path) -> Result<Settings, String> {
+    if let Some(settings) = &seed_file.config {
+        return Ok(settings.clone());
+    }
+
+    let settings_file = sidecar_path(&Some(path.to_path_buf()), "_settings", "json");
+    let settings = util::read_file(&settings_file, "seeds")
+        .map_err(|_| format!("The seed doesn't contain its settings and there is no {} next to it", settings_file.display()))?;
+    Settings::parse(&settings).map_err(|err| format!("{} in {}", err, settings_file.display()))
+}
 
-    world.player.apply_settings(&settings);
+fn diff_seeds(old: PathBuf, new: PathBuf, locations: &Path) -> Result<String, String> {
+    let old = read_seed_file(old)?;
+    let new = read_seed_file(new)?;
+    let locations = lexer::parser::parse_locations(locations, false)?;
+
+    let differences = seed::diff::diff(&old, &new, &locations);
+    if differences.is_empty() {
+        return Ok(String::from("The seeds are identical"));
+    }
+    Ok(differences.iter().map(|difference| difference.to_string()).collect::<Vec<_>>().join("\n"))
+}
 
-    world.player.inventory.grant(Item::Resource(Resource::Health), args.health / 5);
+fn grant_reach_check_items(world: &mut World, args: ReachCheckArgs) -> Result<(), String> {
+    let energy = args.energy.unwrap_or_default();
+    world.player.inventory.grant(Item::Resource(Resource::Health), args.health.unwrap_or_default() / 5);
     #[allow(clippy::cast_possible_truncation)]
-    world.player.inventory.grant(Item::Resource(Resource::Energy), u16::try_from((args.energy * 2.0) as i32).map_err(|_| format!("Invalid energy parameter {}", args.energy))?);
-    world.player.inventory.grant(Item::Resource(Resource::Keystone), args.keystones);
-    world.player.inventory.grant(Item::Resource(Resource::Ore), args.ore);
-    world.player.inventory.grant(Item::SpiritLight(1), u16::try_from(args.spirit_light).unwrap_or(u16::MAX));  // Higher amounts of Spirit Light are irrelevant, just want to accept high values in case the player has that much);
+    world.player.inventory.grant(Item::Resource(Resource::Energy), u16::try_from((energy * 2.0) as i32).map_err(|_| format!("Invalid energy parameter {}", energy))?);
+    world.player.inventory.grant(Item::Resource(Resource::Keystone), args.keystones.unwrap_or_default());
+    world.player.inventory.grant(Item::Resource(Resource::Ore), args.ore.unwrap_or_default());
+    world.player.inventory.grant(Item::SpiritLight(1), u16::try_from(args.spirit_light.unwrap_or_default()).unwrap_or(u16::MAX));  // Higher amounts of Spirit Light are irrelevant, just want to accept high values in case the player has that much);
 
     for item in args.items {
         if let Some(skill) = item.strip_prefix("s:") {
-            let id: u8 = skill.parse().map_err(|_| format!("expected numeric skill id in {}", item))?;
-            world.player.inventory.grant(Item::Skill(Skill::from_id(id).ok_or_else(|| format!("{} is not a valid skill id", id))?), 1);
+            let skill = skill.parse().ok().and_then(Skill::from_id).or_else(|| Skill::from_name(skill)).ok_or_else(|| format!("{} is not a valid skill id or name", skill))?;
+            world.player.inventory.grant(Item::Skill(skill), 1);
         }
         else if let Some(teleporter) = item.strip_prefix("t:") {
-            let id: u8 = teleporter.parse().map_err(|_| format!("expected numeric teleporter id in {}", item))?;
-            world.player.inventory.grant(Item::Teleporter(Teleporter::from_id(id).ok_or_else(|| format!("{} is not a valid teleporter id", id))?), 1);
+            let teleporter = teleporter.parse().ok().and_then(Teleporter::from_id).or_else(|| Teleporter::from_name(teleporter)).ok_or_else(|| format!("{} is not a valid teleporter id or name", teleporter))?;
+            world.player.inventory.grant(Item::Teleporter(teleporter), 1);
         }
         else if let Some(shard) = item.strip_prefix("sh:") {
-            let id: u8 = shard.parse().map_err(|_| format!("expected numeric shard id in {}", item))?;
-            world.player.inventory.grant(Item::Shard(Shard::from_id(id).ok_or_else(|| format!("{} is not a valid shard id", id))?), 1);
+            let shard = shard.parse().ok().and_then(Shard::from_id).or_else(|| Shard::from_name(shard)).ok_or_else(|| format!("{} is not a valid shard id or name", shard))?;
+            world.player.inventory.grant(Item::Shard(shard), 1);
         }
         else if let Some(world_event) = item.strip_prefix("w:") {
             let id: u8 = world_event.parse().map_err(|_| format!("expected numeric world event id in {}", item))?;
@@ -425,18 +850,63 @@ fn reach_check(mut args: ReachCheckArgs) -> Result<String, String> {
 
             world.uber_states.insert(uber_state.identifier, value);
         }
+        else if let Some(named) = Item::from_name(&item) {
+            world.grant_player(named, 1)?;
+        }
         else {
-            return Err(format!("items have to start with s:, t:, sh:, w: or u: (for skill, teleporter, shard, world event or uber state), except found {}", item));
+            return Err(format!("{} is no known item name. Items can also be given by id starting with s:, t:, sh:, w: or u: (for skill, teleporter, shard, world event or uber state)", item));
         }
     }
 
+    Ok(())
+}
+
+fn reach_check(mut args: ReachCheckArgs) -> Result<String, String> {
+    args.seed_file.set_extension("wotwr");
+    let contents = util::read_file(&args.seed_file, "seeds")?;
+
+    let seed_file = SeedFile::parse(&contents).map_err(|err| format!("Failed to read seed {}: {}", args.seed_file.display(), err))?;
+    let settings = seed_settings(&seed_file, &args.seed_file)?;
+    let graph = &lexer::parse_logic(&args.areas, &args.locations, &args.uber_states, &settings.pathsets, false)?;
+
+    let (progressions, json) = (args.progressions, args.json);
+    let (world, dump) = match args.dump.take() {
+        Some(dump) => {
+            let dump = seed::tracker::parse_dump(&util::read_file(&dump, "seeds")?).map_err(|err| format!("Failed to read uber state dump: {}", err))?;
+            (seed::tracker::tracked_world(&seed_file, graph, &settings, &dump), dump)
+        },
+        None => {
+            let mut world = World::new(graph);
+            world.player.apply_settings(&settings);
+            grant_reach_check_items(&mut world, args)?;
+            (world, FxHashMap::default())
+        },
+    };
+
     let spawn = util::settings::read_spawn(&contents)?;
     let spawn = world.graph.find_spawn(&spawn)?;
 
-    let reached = world.graph.reached_locations(&world.player, spawn, &world.uber_states).expect("Invalid Reach Check");
-    let reached: Vec<_> = reached.iter().filter(|&&node| node.can_place()).filter_map(|&node| node.uber_state()).collect();
-    let reached = reached.iter().map(|uber_state| format!("{}", uber_state)).collect::<Vec<_>>();
-    Ok(reached.join(", "))
+    let mut reached = world.graph.reached_locations(&world.player, spawn, &world.uber_states).expect("Invalid Reach Check");
+    reached.retain(|&node| !(node.can_place() && node.uber_state().is_some_and(|uber_state| seed::tracker::is_visited(uber_state, &dump))));
+
+    let progressions = if progressions { Some(seed::tracker::progressions(&world, spawn)?) } else { None };
+
+    if json {
+        return seed::tracker::write_reach_json(&world.player, &reached, progressions.as_deref());
+    }
+
+    let reached = reached.iter()
+        .filter(|&&node| node.can_place())
+        .filter_map(|&node| node.uber_state())
+        .map(|uber_state| format!("{}", uber_state))
+        .collect::<Vec<_>>();
+    let mut output = reached.join(", ");
+
+    for (itemset, unlocked) in progressions.unwrap_or_default() {
+        write!(output, "\n{}: {} new location{}", itemset, unlocked, if unlocked == 1 { "" } else { "s" }).unwrap();
+    }
+
+    Ok(output)
 }
 
 fn main() {
@@ -448,17 +918,18 @@ fn main() {
     }
 
     match args.command {
-        SeedGenCommand::Seed { args, verbose, tostdout } => {
+        SeedGenCommand::Seed { args, verbose, tostdout, format, passphrase } => {
             seedgen::initialize_log(verbose, LevelFilter::Info).unwrap_or_else(|err| eprintln!("Failed to initialize log: {}", err));
 
             let filename = args.filename.clone();
-            match generate_seeds(args) {
-                Ok(seeds) => {
+            match generate_seeds(args, &format) {
+                Ok((seeds, spoilers)) => {
                     if tostdout {
                         write_seeds_to_stdout(seeds);
                     } else {
-                        write_seeds_to_files(seeds, filename).unwrap_or_else(|err| log::error!("{}", err));
+                        write_seeds_to_files(seeds, filename.clone()).unwrap_or_else(|err| log::error!("{}", err));
                     }
+                    write_spoilers_to_files(spoilers, &filename, passphrase).unwrap_or_else(|err| log::error!("{}", err));
                 },
                 Err(err) =>  log::error!("{}", err),
             }
@@ -468,10 +939,80 @@ fn main() {
 
             play_last_seed().unwrap_or_else(|err| log::error!("{}", err));
         },
-        SeedGenCommand::Preset { args } => {
+        SeedGenCommand::Preset { args, subcommand } => {
+            seedgen::initialize_log(false, LevelFilter::Info).unwrap_or_else(|err| eprintln!("Failed to initialize log: {}", err));
+
+            match subcommand {
+                Some(PresetCommand::Show { presets }) => {
+                    match show_presets(presets) {
+                        Ok(settings) => println!("{}", settings),
+                        Err(err) => log::error!("{}", err),
+                    }
+                },
+                None => create_preset(args).unwrap_or_else(|err| log::error!("{}", err)),
+            }
+        },
+        SeedGenCommand::Spoiler { subcommand } => {
             seedgen::initialize_log(false, LevelFilter::Info).unwrap_or_else(|err| eprintln!("Failed to initialize log: {}", err));
 
-            create_preset(args).unwrap_or_else(|err| log::error!("{}", err));
+            match subcommand {
+                SpoilerCommand::Unlock { file, passphrase } => {
+                    unlock_spoiler(file, &passphrase).unwrap_or_else(|err| log::error!("{}", err));
+                },
+                SpoilerCommand::Render { file, format, areas, locations, uber_states } => {
+                    render_spoiler(file, &format, &areas, &locations, &uber_states).unwrap_or_else(|err| log::error!("{}", err));
+                },
+                SpoilerCommand::Map { file, connections, areas, locations, uber_states } => {
+                    draw_map(file, connections, &areas, &locations, &uber_states).unwrap_or_else(|err| log::error!("{}", err));
+                },
+            }
+        },
+        SeedGenCommand::Diff { old, new, locations } => {
+            seedgen::initialize_log(false, LevelFilter::Info).unwrap_or_else(|err| eprintln!("Failed to initialize log: {}", err));
+
+            match diff_seeds(old, new, &locations) {
+                Ok(differences) => println!("{}", differences),
+                Err(err) => log::error!("{}", err),
+            }
+        },
+        SeedGenCommand::Logic { subcommand } => {
+            seedgen::initialize_log(false, LevelFilter::Info).unwrap_or_else(|err| eprintln!("Failed to initialize log: {}", err));
+
+            match subcommand {
+                LogicCommand::Pathsets { areas, locations, uber_states } => {
+                    match list_pathsets(&areas, &locations, &uber_states) {
+                        Ok(pathsets) => println!("{}", pathsets),
+                        Err(err) => log::error!("{}", err),
+                    }
+                },
+                LogicCommand::Test { files, areas, locations, uber_states } => {
+                    match logic_test::run_tests(&files, &areas, &locations, &uber_states) {
+                        Ok(report) => {
+                            println!("{}", report.output);
+                            if report.failed > 0 { std::process::exit(1); }
+                        },
+                        Err(err) => {
+                            log::error!("{}", err);
+                            std::process::exit(1);
+                        },
+                    }
+                },
+                LogicCommand::Diff { old, new, logic, locations, uber_states } => {
+                    match diff_logic(&old, &new, &locations, &uber_states, &logic) {
+                        Ok(diff) => println!("{}", diff),
+                        Err(err) => {
+                            log::error!("{}", err);
+                            std::process::exit(1);
+                        },
+                    }
+                },
+                LogicCommand::Fmt { areas, check } => {
+                    if let Err(err) = format_logic(&areas, check) {
+                        log::error!("{}", err);
+                        std::process::exit(1);
+                    }
+                },
+            }
         },
         SeedGenCommand::Headers { headers, subcommand } => {
             seedgen::initialize_log(false, LevelFilter::Info).unwrap_or_else(|err| eprintln!("Failed to initialize log: {}", err));
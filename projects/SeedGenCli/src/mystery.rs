@@ -0,0 +1,120 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use rand::{
+    Rng,
+    distributions::{Distribution, WeightedIndex},
+};
+use serde::Deserialize;
+
+use seedgen::util::{self, Pathsets, settings::Settings};
+
+use crate::{parse_goalmodes, parse_spawn};
+
+/// Chances to roll each setting in a mystery seed
+///
+/// pathsets, goals and spawns use the same names as the command line flags
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Weights {
+    /// chance for each pathset to be included
+    pub logic: BTreeMap<String, f64>,
+    /// chance for each goal mode to be included
+    pub goals: BTreeMap<String, f64>,
+    /// relative weights of the spawns to choose from
+    pub spawn: BTreeMap<String, f64>,
+    /// chance for each header to be included
+    pub headers: BTreeMap<PathBuf, f64>,
+    /// keep the rolled settings out of the seed file and write them to a separate file instead
+    ///
+    /// the seed still reveals some of them, since its flags line lists the rolled goal modes and random spawn
+    pub hidden: bool,
+}
+
+impl Weights {
+    pub fn from_file(path: &Path) -> Result<Weights, String> {
+        let mut path = path.to_path_buf();
+        if path.extension().is_none() {
+            path.set_extension("json");
+        }
+        let content = util::read_file(&path, "presets")?;
+        serde_json::from_str(&content).map_err(|err| format!("Failed to read mystery weights from {}: {}", path.display(), err))
+    }
+
+    pub fn roll<R>(&self, rng: &mut R) -> Result<Settings, String>
+    where R: Rng + ?Sized
+    {
+        let logic = roll_each(&self.logic, rng)?;
+        let goals = roll_each(&self.goals, rng)?;
+        let header_list = roll_each(&self.headers, rng)?;
+
+        let mut settings = Settings {
+            pathsets: Pathsets::from_names(&logic),
+            goalmodes: parse_goalmodes(&goals),
+            header_list,
+            ..Settings::default()
+        };
+
+        if !self.spawn.is_empty() {
+            let distribution = WeightedIndex::new(self.spawn.values()).map_err(|err| format!("Invalid spawn weights: {}", err))?;
+            let spawn = self.spawn.keys().nth(distribution.sample(rng)).unwrap();
+            settings.spawn_loc = parse_spawn(spawn.clone());
+        }
+
+        Ok(settings)
+    }
+}
+
+fn roll_each<T, R>(chances: &BTreeMap<T, f64>, rng: &mut R) -> Result<Vec<T>, String>
+where
+    T: Clone + std::fmt::Debug,
+    R: Rng + ?Sized,
+{
+    let mut rolled = Vec::new();
+    for (option, &chance) in chances {
+        if !(0.0..=1.0).contains(&chance) {
+            return Err(format!("Invalid chance {} for {:?}, expected a value between 0 and 1", chance, option));
+        }
+        if rng.gen_bool(chance) {
+            rolled.push(option.clone());
+        }
+    }
+    Ok(rolled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand_seeder::Seeder;
+    use seedgen::util::settings::Spawn;
+
+    fn weights(json: &str) -> Weights {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn mystery_rolls() {
+        let mystery = weights(r#"{
+            "logic": { "gorlek": 0.5, "glitch": 0.1 },
+            "goals": { "trees": 0.5, "wisps": 0.5, "quests": 0.5 },
+            "spawn": { "MarshSpawn.Main": 1, "random": 1, "fullyrandom": 1 },
+            "headers": { "black_market": 0.5, "no_rain": 0.5 }
+        }"#);
+        for seed in ["1", "mystery", "12345678"] {
+            let roll = || mystery.roll(&mut Seeder::from(seed).make_rng::<StdRng>()).unwrap();
+            assert_eq!(roll(), roll());
+        }
+
+        assert!(weights(r#"{ "logic": { "gorlek": 1.5 } }"#).roll(&mut Seeder::from("1").make_rng::<StdRng>()).is_err());
+        assert!(weights(r#"{ "headers": { "qol": -0.1 } }"#).roll(&mut Seeder::from("1").make_rng::<StdRng>()).is_err());
+
+        let mystery = weights(r#"{ "spawn": { "MarshSpawn.Main": 0, "random": 1, "fullyrandom": 0 } }"#);
+        let mut rng: StdRng = Seeder::from("spawns").make_rng();
+        for _ in 0..100 {
+            assert_eq!(mystery.roll(&mut rng).unwrap().spawn_loc, Spawn::Random);
+        }
+    }
+}
@@ -4,9 +4,40 @@ use rustc_hash::FxHashSet;
 
 use crate::util::{
     Pathsets, Pathset, GoalMode,
-    settings::Settings,
+    settings::{Settings, Preset},
 };
 
+/// Resolves a built-in preset or preset file, including all the presets it extends
+pub fn read(name: &str) -> Result<Settings, String> {
+    resolve(name, &mut Vec::new())
+}
+fn resolve(name: &str, chain: &mut Vec<String>) -> Result<Settings, String> {
+    match name {
+        "moki" => return Ok(moki()),
+        "gorlek" => return Ok(gorlek()),
+        "gorlekg" | "gorlek_glitch" => return Ok(gorlek_glitch()),
+        _ => {},
+    }
+
+    if chain.iter().any(|parent| parent == name) {
+        return Err(format!("Preset {} extends itself: {} -> {}", name, chain.join(" -> "), name));
+    }
+
+    let mut path = PathBuf::from(name);
+    path.set_extension("json");
+    let preset = Preset::from_file(&path)?;
+
+    chain.push(name.to_string());
+    let mut settings = Settings::default();
+    for parent in &preset.extends {
+        settings.inherit(resolve(parent, chain)?);
+    }
+    chain.pop();
+
+    settings.apply(preset);
+    Ok(settings)
+}
+
 pub fn moki() -> Settings {
     let mut goalmodes = FxHashSet::default();
     goalmodes.insert(GoalMode::Wisps);
@@ -0,0 +1,432 @@
+pub mod diff;
+pub mod render;
+pub mod map;
+pub mod tracker;
+
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::inventory::Item;
+use crate::headers::parser::parse_pickup;
+use crate::util::{
+    self,
+    Position,
+    settings::Settings,
+    uberstate::UberState,
+};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SeedSpawn {
+    pub position: Position,
+    pub identifier: String,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SeedPlacement {
+    pub uber_state: UberState,
+    pub item: Item,
+    /// describes the item and its location, only written if the seed should contain spoilers
+    pub spoiler: Option<PlacementSpoiler>,
+}
+impl SeedPlacement {
+    /// Whether this is the spawn message showing the seed hash, which differs between any two seeds
+    pub fn is_seed_hash(&self) -> bool {
+        self.uber_state == UberState::spawn() && matches!(&self.item, Item::Message(message) if message.starts_with("Seed hash: "))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PlacementSpoiler {
+    /// the item's name, which may be a custom name from the headers
+    pub item: String,
+    /// the location's identifier, or its uber state if it isn't a known location
+    pub location: String,
+    pub position: Option<Position>,
+    pub zone: Option<String>,
+}
+impl fmt::Display for PlacementSpoiler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.location.is_empty() {
+            return write!(f, "{}", self.item);
+        }
+        write!(f, "{} from ", util::with_leading_spaces(&self.item, 30))?;
+        match &self.position {
+            Some(position) => {
+                let mut location = self.location.clone();
+                util::add_trailing_spaces(&mut location, 33);
+                let mut position = format!("({})", position);
+                util::add_trailing_spaces(&mut position, 15);
+                write!(f, "{}  {} {}", location, position, self.zone.as_deref().unwrap_or_default())
+            },
+            None => write!(f, "{}", self.location),
+        }
+    }
+}
+impl PlacementSpoiler {
+    /// Reads a spoiler comment, comments that don't follow the usual format are kept as the item name
+    pub fn parse(spoiler: &str) -> PlacementSpoiler {
+        let unknown = || PlacementSpoiler {
+            item: spoiler.to_string(),
+            location: String::new(),
+            position: None,
+            zone: None,
+        };
+
+        let (item, location) = match spoiler.rfind(" from ") {
+            Some(index) => (spoiler[..index].trim(), spoiler[index + 6..].trim()),
+            None => return unknown(),
+        };
+        if item.is_empty() || location.is_empty() { return unknown(); }
+
+        let (location, position, zone) = match (location.find('('), location.find(')')) {
+            (Some(start), Some(end)) if start < end => {
+                let position = match parse_position(&location[start + 1..end]) {
+                    Ok(position) => position,
+                    Err(_) => return unknown(),
+                };
+                let zone = location[end + 1..].trim();
+                (location[..start].trim(), Some(position), if zone.is_empty() { None } else { Some(zone.to_string()) })
+            },
+            _ => (location, None, None),
+        };
+
+        PlacementSpoiler {
+            item: item.to_string(),
+            location: location.to_string(),
+            position,
+            zone,
+        }
+    }
+}
+
+/// The contents of a .wotwr file
+#[derive(Debug, PartialEq, Default)]
+pub struct SeedFile {
+    pub flags: Vec<String>,
+    pub spawn: Option<SeedSpawn>,
+    pub placements: Vec<SeedPlacement>,
+    /// lines from the headers which are passed through as they are
+    pub headers: Vec<String>,
+    pub seed: Option<String>,
+    pub config: Option<Settings>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonSeed<'a> {
+    seed: &'a Option<String>,
+    flags: &'a [String],
+    spawn: Option<JsonSpawn<'a>>,
+    placements: Vec<JsonPlacement<'a>>,
+    headers: &'a [String],
+    settings: Option<Value>,
+}
+#[derive(Serialize)]
+struct JsonSpawn<'a> {
+    identifier: &'a str,
+    position: &'a Position,
+}
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonPlacement<'a> {
+    uber_state: String,
+    code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    item: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<JsonLocation<'a>>,
+}
+#[derive(Serialize)]
+struct JsonLocation<'a> {
+    name: &'a str,
+    zone: &'a Option<String>,
+    position: &'a Option<Position>,
+}
+
+enum Section {
+    Start,
+    Placements,
+    Headers,
+}
+
+impl SeedFile {
+    pub fn parse(contents: &str) -> Result<SeedFile, String> {
+        let mut seed_file = SeedFile::default();
+        let mut section = Section::Start;
+
+        for (index, line) in contents.lines().enumerate() {
+            let line_number = index + 1;
+
+            if let Some(seed) = line.strip_prefix("// Seed: ") {
+                seed_file.seed = Some(seed.to_string());
+                continue;
+            }
+            if let Some(config) = line.strip_prefix("// Config: ") {
+                seed_file.config = Some(Settings::parse(config).map_err(|err| format!("{} in line {}", err, line_number))?);
+                continue;
+            }
+
+            if let Section::Start = section {
+                if let Some(flags) = line.strip_prefix("Flags:") {
+                    seed_file.flags = flags.split(',').map(|flag| flag.trim().to_string()).filter(|flag| !flag.is_empty()).collect();
+                    continue;
+                } else if let Some(spawn) = line.strip_prefix("Spawn:") {
+                    seed_file.spawn = Some(parse_spawn(spawn).map_err(|err| format!("{} in line {}", err, line_number))?);
+                    continue;
+                } else if line.trim().is_empty() {
+                    section = Section::Placements;
+                    continue;
+                }
+                section = Section::Placements;  // older seeds may have no empty line before the placements
+            }
+
+            match section {
+                Section::Placements => {
+                    if line.trim().is_empty() {
+                        section = Section::Headers;
+                    } else {
+                        let placement = parse_placement(line).map_err(|err| format!("{} in line {}", err, line_number))?;
+                        seed_file.placements.push(placement);
+                    }
+                },
+                Section::Headers => seed_file.headers.push(line.to_string()),
+                Section::Start => unreachable!(),
+            }
+        }
+
+        Ok(seed_file)
+    }
+
+    pub fn write(&self, spoilers: bool) -> Result<String, String> {
+        let mut contents = String::with_capacity(self.placements.len() * if spoilers { 140 } else { 20 });
+
+        if !self.flags.is_empty() {
+            contents += &format!("Flags: {}\n", self.flags.join(", "));
+        }
+        if let Some(spawn) = &self.spawn {
+            contents += &format!("Spawn: {}  // {}\n", spawn.position, spawn.identifier);
+        }
+
+        contents.push('\n');
+        for placement in &self.placements {
+            let mut placement_line = format!("{}|{}", placement.uber_state, placement.item.code());
+
+            if let (true, Some(spoiler)) = (spoilers, &placement.spoiler) {
+                util::add_trailing_spaces(&mut placement_line, 42);
+                placement_line += &format!("  // {}", spoiler);
+            }
+
+            placement_line.push('\n');
+            contents += &placement_line;
+        }
+        contents.push('\n');
+
+        for header_line in &self.headers {
+            contents += header_line;
+            contents.push('\n');
+        }
+
+        if let Some(seed) = &self.seed {
+            contents += &format!("// Seed: {}\n", seed);
+        }
+        if let Some(config) = &self.config {
+            contents += &format!("// Config: {}\n", Settings::write(config)?);
+        }
+        if contents.ends_with('\n') {
+            contents.pop();
+        }
+
+        Ok(contents)
+    }
+
+    /// Writes the seed as a json document for other tools, item names and locations are only included with spoilers
+    pub fn write_json(&self, spoilers: bool) -> Result<String, String> {
+        let placements = self.placements.iter().map(|placement| {
+            let spoiler = placement.spoiler.as_ref().filter(|_| spoilers);
+            JsonPlacement {
+                uber_state: placement.uber_state.to_string(),
+                code: placement.item.code(),
+                item: spoiler.map(|spoiler| &spoiler.item[..]),
+                location: spoiler.filter(|spoiler| !spoiler.location.is_empty()).map(|spoiler| JsonLocation {
+                    name: &spoiler.location,
+                    zone: &spoiler.zone,
+                    position: &spoiler.position,
+                }),
+            }
+        }).collect();
+
+        let json = JsonSeed {
+            seed: &self.seed,
+            flags: &self.flags,
+            spawn: self.spawn.as_ref().map(|spawn| JsonSpawn {
+                identifier: &spawn.identifier,
+                position: &spawn.position,
+            }),
+            placements,
+            headers: &self.headers,
+            settings: self.config.as_ref().map(Settings::write_value).transpose()?,
+        };
+
+        serde_json::to_string_pretty(&json).map_err(|err| format!("Failed to write seed as json: {}", err))
+    }
+}
+
+fn parse_spawn(spawn: &str) -> Result<SeedSpawn, String> {
+    let mut parts = spawn.splitn(2, "//");
+    let position = parts.next().unwrap();
+    let identifier = parts.next().ok_or_else(|| String::from("missing spawn name"))?.trim().to_string();
+
+    Ok(SeedSpawn {
+        position: parse_position(position).map_err(|err| format!("invalid spawn {}", err))?,
+        identifier,
+    })
+}
+
+fn parse_position(position: &str) -> Result<Position, String> {
+    let mut coordinates = position.split(',').map(|coordinate| coordinate.trim().parse::<i16>());
+    let x = coordinates.next().and_then(Result::ok).ok_or_else(|| format!("position {}", position.trim()))?;
+    let y = coordinates.next().and_then(Result::ok).ok_or_else(|| format!("position {}", position.trim()))?;
+    if coordinates.next().is_some() { return Err(format!("position {}", position.trim())); }
+
+    Ok(Position { x, y })
+}
+
+// Splits off the spoiler comment the writer puts after a placement
+//
+// Messages may contain the separator themselves, so it only counts if a spoiler in the usual format follows.
+// That spoiler names the item, which only contains the separator as well if it is the message itself
+fn split_spoiler(line: &str) -> (&str, Option<PlacementSpoiler>) {
+    for (index, separator) in line.match_indices("  // ") {
+        let placement = line[..index].trim();
+        let spoiler = PlacementSpoiler::parse(&line[index + separator.len()..]);
+        if spoiler.location.is_empty() { continue; }
+        if !spoiler.item.contains(separator) || placement.splitn(3, '|').nth(2) == Some(spoiler.item.as_str()) {
+            return (placement, Some(spoiler));
+        }
+    }
+    (line.trim(), None)
+}
+
+fn parse_placement(line: &str) -> Result<SeedPlacement, String> {
+    let (placement, spoiler) = split_spoiler(line);
+
+    let mut parts = placement.splitn(3, '|');
+    let uber_group = parts.next().unwrap();
+    let uber_id = parts.next().ok_or_else(|| format!("expected uber id in placement {}", placement))?;
+    let pickup = parts.next().ok_or_else(|| format!("expected pickup in placement {}", placement))?;
+
+    let uber_state = UberState::from_parts(uber_group, uber_id)?;
+    let item = parse_pickup(pickup, uber_state.is_shop())?;
+
+    Ok(SeedPlacement {
+        uber_state,
+        item,
+        spoiler,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use crate::{lexer, util::{Skill, Pathsets}};
+
+    #[test]
+    fn seed_file() {
+        let seed = "Flags: ForceTrees, RandomSpawn\n\
+            Spawn: -1308, -3675  // InnerWellspring.Teleporter\n\
+            \n\
+            3|0|6|f=420|instant                         //                6|f=420|instant from 3|0\n\
+            37858|59022|2|102                           //                           Dash from InnerWellspring.WaterEscapeExit    (-1277, -3662)  Inkwater Marsh\n\
+            48248|1|0|50\n\
+            \n\
+            /// Header\n\
+            9|0|8|9|0|int|0  // for temporary usage\n\
+            \n\
+            // Seed: h1\n\
+            // Config: {\"schemaVersion\":1,\"worlds\":1,\"players\":[],\"pathsets\":[\"Moki\"],\"goalmodes\":[\"Trees\"],\"spawnLoc\":\"Random\",\"spoilers\":true,\"webConn\":false,\"hard\":false,\"headerList\":[]}";
+
+        let seed_file = SeedFile::parse(seed).unwrap();
+        assert_eq!(seed_file.flags, vec!["ForceTrees", "RandomSpawn"]);
+        assert_eq!(seed_file.spawn.as_ref().unwrap().identifier, "InnerWellspring.Teleporter");
+        assert_eq!(seed_file.placements.len(), 3);
+        assert_eq!(seed_file.placements[1].item, Item::Skill(Skill::Dash));
+        assert_eq!(seed_file.placements[1].spoiler, Some(PlacementSpoiler {
+            item: String::from("Dash"),
+            location: String::from("InnerWellspring.WaterEscapeExit"),
+            position: Some(Position { x: -1277, y: -3662 }),
+            zone: Some(String::from("Inkwater Marsh")),
+        }));
+        assert_eq!(seed_file.placements[2].spoiler, None);
+        assert_eq!(seed_file.headers, vec!["/// Header", "9|0|8|9|0|int|0  // for temporary usage", ""]);
+        assert_eq!(seed_file.seed, Some(String::from("h1")));
+        assert_eq!(seed_file.config.as_ref().unwrap().spawn_loc, util::settings::Spawn::Random);
+
+        assert_eq!(seed_file.write(true).unwrap(), seed);
+        assert_eq!(SeedFile::parse(&seed_file.write(false).unwrap()).unwrap().placements.iter().filter(|placement| placement.spoiler.is_some()).count(), 0);
+
+        let message = "\n3|0|6|Rules at https://wotw.orirando.com  // 6|Rules at https://wotw.orirando.com from 3|0\n";
+        let seed_file = SeedFile::parse(message).unwrap();
+        assert_eq!(seed_file.placements[0].item.code(), "6|Rules at https://wotw.orirando.com");
+        assert_eq!(seed_file.placements[0].spoiler.as_ref().unwrap().item, "6|Rules at https://wotw.orirando.com");
+        let written = seed_file.write(true).unwrap();
+        assert_eq!(SeedFile::parse(&written).unwrap(), seed_file);
+        assert_eq!(SeedFile::parse(&seed_file.write(false).unwrap()).unwrap().placements[0].item, seed_file.placements[0].item);
+
+        let message = "\n3|0|6|Left  // Right  // 6|Left  // Right from 3|0\n";
+        let seed_file = SeedFile::parse(message).unwrap();
+        assert_eq!(seed_file.placements[0].item.code(), "6|Left  // Right");
+        assert_eq!(seed_file.placements[0].spoiler.as_ref().unwrap().location, "3|0");
+        assert_eq!(SeedFile::parse(&seed_file.write(true).unwrap()).unwrap(), seed_file);
+        let spoilerless = SeedFile::parse(&seed_file.write(false).unwrap()).unwrap();
+        assert_eq!(spoilerless.placements[0].item, seed_file.placements[0].item);
+        assert_eq!(spoilerless.placements[0].spoiler, None);
+
+        assert!(SeedFile::parse("\n3|0|2").is_err());
+        assert!(SeedFile::parse("Spawn: -1308  // InnerWellspring.Teleporter").is_err());
+    }
+
+    #[test]
+    fn generated_seed_file() {
+        let graph = lexer::parse_logic(&PathBuf::from("areas.wotw"), &PathBuf::from("loc_data.csv"), &PathBuf::from("state_data.csv"), &Pathsets::default(), false).unwrap();
+        let settings = Settings {
+            spawn_loc: util::settings::Spawn::Random,
+            ..Settings::default()
+        };
+        let seed_files = crate::generate_seed(&graph, &settings, &[String::from("3|0|2|100")], Some(String::from("round trip"))).unwrap();
+
+        for seed_file in seed_files {
+            for spoilers in &[true, false] {
+                let written = seed_file.write(*spoilers).unwrap();
+                let read = SeedFile::parse(&written).unwrap();
+                assert_eq!(read.write(*spoilers).unwrap(), written);
+            }
+            assert_eq!(SeedFile::parse(&seed_file.write(true).unwrap()).unwrap(), seed_file);
+        }
+    }
+
+    #[test]
+    fn seed_json() {
+        let seed_file = SeedFile::parse("Spawn: -1308, -3675  // InnerWellspring.Teleporter\n\n\
+            37858|59022|2|102                           //                           Dash from InnerWellspring.WaterEscapeExit    (-1277, -3662)  Inkwater Marsh\n\
+            \n// Seed: json").unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&seed_file.write_json(true).unwrap()).unwrap();
+        assert_eq!(json["seed"], "json");
+        assert_eq!(json["spawn"]["position"]["x"], -1308);
+        assert_eq!(json["settings"], serde_json::Value::Null);
+        let placement = &json["placements"][0];
+        assert_eq!(placement["uberState"], "37858|59022");
+        assert_eq!(placement["code"], "2|102");
+        assert_eq!(placement["item"], "Dash");
+        assert_eq!(placement["location"]["name"], "InnerWellspring.WaterEscapeExit");
+        assert_eq!(placement["location"]["zone"], "Inkwater Marsh");
+        assert_eq!(placement["location"]["position"]["y"], -3662);
+
+        let json: serde_json::Value = serde_json::from_str(&seed_file.write_json(false).unwrap()).unwrap();
+        assert!(json["placements"][0].get("item").is_none());
+        assert!(json["placements"][0].get("location").is_none());
+    }
+}
@@ -0,0 +1,195 @@
+use std::fmt;
+
+use rustc_hash::FxHashMap;
+use serde_json::Value;
+
+use super::{SeedFile, SeedSpawn};
+use crate::inventory::Item;
+use crate::lexer::parser::Location;
+use crate::util::{
+    settings::Settings,
+    uberstate::UberState,
+};
+
+#[derive(Debug, PartialEq)]
+pub enum Difference {
+    FlagAdded(String),
+    FlagRemoved(String),
+    Spawn(Option<String>, Option<String>),
+    Setting(String, String, String),
+    Moved(Item, String, String),
+    Changed(String, Item, Item),
+    Added(String, Item),
+    Removed(String, Item),
+    Headers(usize, usize),
+    Seed(Option<String>, Option<String>),
+}
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let or_none = |value: &Option<String>| value.clone().unwrap_or_else(|| String::from("none"));
+        match self {
+            Difference::FlagAdded(flag) => write!(f, "Added flag {}", flag),
+            Difference::FlagRemoved(flag) => write!(f, "Removed flag {}", flag),
+            Difference::Spawn(old, new) => write!(f, "Spawn changed from {} to {}", or_none(old), or_none(new)),
+            Difference::Setting(setting, old, new) => write!(f, "Setting {} changed from {} to {}", setting, old, new),
+            Difference::Moved(item, old, new) => write!(f, "{} moved from {} to {}", item, old, new),
+            Difference::Changed(location, old, new) => write!(f, "{} changed from {} to {}", location, old, new),
+            Difference::Added(location, item) => write!(f, "{} added at {}", item, location),
+            Difference::Removed(location, item) => write!(f, "{} removed from {}", item, location),
+            Difference::Headers(added, removed) => write!(f, "Header lines changed: {} added, {} removed", added, removed),
+            Difference::Seed(old, new) => write!(f, "Seed changed from {} to {}", or_none(old), or_none(new)),
+        }
+    }
+}
+
+fn location_name(uber_state: &UberState, locations: &[Location]) -> String {
+    if *uber_state == UberState::spawn() {
+        return String::from("Spawn");
+    }
+    locations.iter()
+        .find(|location| location.uber_state == *uber_state)
+        .map_or_else(|| uber_state.to_string(), |location| location.name.clone())
+}
+
+fn spawn_name(spawn: &Option<SeedSpawn>) -> Option<String> {
+    spawn.as_ref().map(|spawn| spawn.identifier.clone())
+}
+
+fn settings_values(settings: &Option<Settings>) -> FxHashMap<String, Value> {
+    match settings.as_ref().map(serde_json::to_value) {
+        Some(Ok(Value::Object(object))) => object.into_iter().collect(),
+        _ => FxHashMap::default(),
+    }
+}
+
+// Items on each location, with the locations in the order they first appear
+// The seed hash is left out since it would always show up as changed
+fn placements_by_location(seed: &SeedFile) -> Vec<(&UberState, Vec<&Item>)> {
+    let mut locations: Vec<(&UberState, Vec<&Item>)> = Vec::new();
+    for placement in seed.placements.iter().filter(|placement| !placement.is_seed_hash()) {
+        match locations.iter_mut().find(|(uber_state, _)| *uber_state == &placement.uber_state) {
+            Some((_, items)) => items.push(&placement.item),
+            None => locations.push((&placement.uber_state, vec![&placement.item])),
+        }
+    }
+    locations
+}
+
+/// Lists the differences between two seeds, naming locations after the given location data where possible
+pub fn diff(old: &SeedFile, new: &SeedFile, locations: &[Location]) -> Vec<Difference> {
+    let mut differences = Vec::new();
+
+    for flag in &new.flags {
+        if !old.flags.contains(flag) { differences.push(Difference::FlagAdded(flag.clone())); }
+    }
+    for flag in &old.flags {
+        if !new.flags.contains(flag) { differences.push(Difference::FlagRemoved(flag.clone())); }
+    }
+
+    let (old_spawn, new_spawn) = (spawn_name(&old.spawn), spawn_name(&new.spawn));
+    if old_spawn != new_spawn {
+        differences.push(Difference::Spawn(old_spawn, new_spawn));
+    }
+
+    let old_settings = settings_values(&old.config);
+    let mut new_settings = settings_values(&new.config);
+    let mut keys = old_settings.keys().chain(new_settings.keys()).cloned().collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        let old_value = old_settings.get(&key).map_or_else(|| String::from("none"), Value::to_string);
+        let new_value = new_settings.remove(&key).map_or_else(|| String::from("none"), |value| value.to_string());
+        if old_value != new_value {
+            differences.push(Difference::Setting(key, old_value, new_value));
+        }
+    }
+
+    let old_placements = placements_by_location(old);
+    let new_placements = placements_by_location(new);
+
+    // items which are no longer / newly placed on each location
+    let mut removed: Vec<(&UberState, &Item)> = Vec::new();
+    let mut added: Vec<(&UberState, &Item)> = Vec::new();
+    for (uber_state, items) in &old_placements {
+        let mut new_items = new_placements.iter().find(|(new_state, _)| new_state == uber_state).map_or_else(Vec::new, |(_, items)| items.clone());
+        for item in items {
+            match new_items.iter().position(|new_item| new_item == item) {
+                Some(index) => { new_items.remove(index); },
+                None => removed.push((uber_state, item)),
+            }
+        }
+        added.extend(new_items.into_iter().map(|item| (*uber_state, item)));
+    }
+    for (uber_state, items) in &new_placements {
+        if !old_placements.iter().any(|(old_state, _)| old_state == uber_state) {
+            added.extend(items.iter().map(|item| (*uber_state, *item)));
+        }
+    }
+
+    removed.retain(|(old_state, item)| {
+        match added.iter().position(|(_, added_item)| added_item == item) {
+            Some(index) => {
+                let (new_state, _) = added.remove(index);
+                differences.push(Difference::Moved((*item).clone(), location_name(old_state, locations), location_name(new_state, locations)));
+                false
+            },
+            None => true,
+        }
+    });
+    for (old_state, old_item) in removed {
+        match added.iter().position(|(new_state, _)| *new_state == old_state) {
+            Some(index) => {
+                let (_, new_item) = added.remove(index);
+                differences.push(Difference::Changed(location_name(old_state, locations), old_item.clone(), new_item.clone()));
+            },
+            None => differences.push(Difference::Removed(location_name(old_state, locations), old_item.clone())),
+        }
+    }
+    for (new_state, new_item) in added {
+        differences.push(Difference::Added(location_name(new_state, locations), new_item.clone()));
+    }
+
+    let added_headers = new.headers.iter().filter(|line| !old.headers.contains(line)).count();
+    let removed_headers = old.headers.iter().filter(|line| !new.headers.contains(line)).count();
+    if added_headers > 0 || removed_headers > 0 {
+        differences.push(Difference::Headers(added_headers, removed_headers));
+    }
+
+    if old.seed != new.seed {
+        differences.push(Difference::Seed(old.seed.clone(), new.seed.clone()));
+    }
+
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{Position, Skill, Shard};
+
+    #[test]
+    fn seed_diff() {
+        let old = SeedFile::parse("Flags: ForceTrees\n\n3|0|6|Seed hash: Flap Howl Tuley Hand|f=600\n3|0|2|8\n1|1|2|100\n1|2|3|2\n1|3|0|50\n\n// Seed: old").unwrap();
+        let new = SeedFile::parse("Flags: ForceWisps\nSpawn: -307, -4153  // GladesTown.Teleporter\n\n3|0|6|Seed hash: Wisp Bash Moki Leaf|f=600\n3|0|2|8\n1|2|2|100\n1|3|3|3\n1|4|0|20\n\n!!remove 2|8\n// Seed: new").unwrap();
+        let locations = vec![Location {
+            name: String::from("MarshSpawn.RockHC"),
+            zone: String::from("Inkwater Marsh"),
+            uber_state: UberState::from_parts("1", "1").unwrap(),
+            position: Position::default(),
+        }];
+
+        let differences = diff(&old, &new, &locations);
+        assert_eq!(differences, vec![
+            Difference::FlagAdded(String::from("ForceWisps")),
+            Difference::FlagRemoved(String::from("ForceTrees")),
+            Difference::Spawn(None, Some(String::from("GladesTown.Teleporter"))),
+            Difference::Moved(Item::Skill(Skill::Sword), String::from("MarshSpawn.RockHC"), String::from("1|2")),
+            Difference::Removed(String::from("1|2"), Item::Shard(Shard::TripleJump)),
+            Difference::Changed(String::from("1|3"), Item::SpiritLight(50), Item::Shard(Shard::Wingclip)),
+            Difference::Added(String::from("1|4"), Item::SpiritLight(20)),
+            Difference::Headers(1, 0),
+            Difference::Seed(Some(String::from("old")), Some(String::from("new"))),
+        ]);
+        assert!(diff(&old, &old, &locations).is_empty());
+    }
+}
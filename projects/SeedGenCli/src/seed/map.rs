@@ -0,0 +1,164 @@
+use std::fmt::Write;
+
+use super::SeedFile;
+use crate::inventory::Item;
+use crate::world::graph::{Graph, Node};
+use crate::util::{
+    Position,
+    uberstate::UberState,
+    constants::DEFAULT_SPAWN,
+};
+
+/// Categories items get colored by on the map, more important categories first
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum ItemCategory {
+    Skill,
+    Teleporter,
+    Shard,
+    Resource,
+    SpiritLight,
+    Other,
+}
+impl ItemCategory {
+    pub fn of(item: &Item) -> ItemCategory {
+        match item {
+            Item::Skill(_) | Item::Water => ItemCategory::Skill,
+            Item::Teleporter(_) => ItemCategory::Teleporter,
+            Item::Shard(_) => ItemCategory::Shard,
+            Item::Resource(_) => ItemCategory::Resource,
+            Item::SpiritLight(_) => ItemCategory::SpiritLight,
+            _ => ItemCategory::Other,
+        }
+    }
+
+    fn color(self) -> &'static str {
+        match self {
+            ItemCategory::Skill => "#e03c31",
+            ItemCategory::Teleporter => "#2f80ed",
+            ItemCategory::Shard => "#9b51e0",
+            ItemCategory::Resource => "#27ae60",
+            ItemCategory::SpiritLight => "#f2c94c",
+            ItemCategory::Other => "#828282",
+        }
+    }
+}
+
+const CATEGORIES: [ItemCategory; 6] = [ItemCategory::Skill, ItemCategory::Teleporter, ItemCategory::Shard, ItemCategory::Resource, ItemCategory::SpiritLight, ItemCategory::Other];
+// Empty space around the locations, in game units
+const MARGIN: i32 = 50;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Shop locations are all at 0, 0
+fn map_position(node: &Node) -> Option<&Position> {
+    if node.uber_state().is_some_and(UberState::is_shop) { return None; }
+    node.position()
+}
+
+// The game's y axis points up, svg's points down
+fn point(position: &Position) -> (i32, i32) {
+    (i32::from(position.x), -i32::from(position.y))
+}
+
+/// Draws every location of the graph with the items the seed places there, colored by the most important item category
+///
+/// Shops are left out since they have no position in the world
+///
+/// With connections, the paths between anchors are drawn as well
+pub fn render_svg(seed_file: &SeedFile, graph: &Graph, connections: bool) -> Result<String, String> {
+    let spawn = match &seed_file.spawn {
+        Some(spawn) => spawn.position.clone(),
+        None => graph.find_spawn(DEFAULT_SPAWN)?.position().cloned().ok_or_else(|| format!("{} has no position", DEFAULT_SPAWN))?,
+    };
+
+    let positions = graph.nodes.iter().filter_map(map_position).map(point).chain(std::iter::once(point(&spawn)));
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (i32::MAX, i32::MAX, i32::MIN, i32::MIN);
+    for (x, y) in positions {
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+    let (width, height) = (max_x - min_x + 2 * MARGIN, max_y - min_y + 2 * MARGIN);
+
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" width=\"{}\" height=\"{}\">\n", min_x - MARGIN, min_y - MARGIN, width, height, width, height);
+    writeln!(svg, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#1b1b2f\"/>", min_x - MARGIN, min_y - MARGIN, width, height).unwrap();
+
+    if connections {
+        svg += "<g stroke=\"#4f4f6f\" stroke-width=\"1\">\n";
+        for node in &graph.nodes {
+            if let (Node::Anchor(anchor), Some(from)) = (node, map_position(node)) {
+                for connection in &anchor.connections {
+                    if let Some(to) = map_position(&graph.nodes[connection.to]) {
+                        let ((x1, y1), (x2, y2)) = (point(from), point(to));
+                        writeln!(svg, "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>", x1, y1, x2, y2).unwrap();
+                    }
+                }
+            }
+        }
+        svg += "</g>\n";
+    }
+
+    svg += "<g stroke=\"#000000\" stroke-width=\"1\">\n";
+    for node in graph.nodes.iter().filter(|node| node.can_place()) {
+        let (position, uber_state) = match (map_position(node), node.uber_state()) {
+            (Some(position), Some(uber_state)) => (position, uber_state),
+            _ => continue,
+        };
+        let placements = seed_file.placements.iter().filter(|placement| &placement.uber_state == uber_state).collect::<Vec<_>>();
+
+        let category = placements.iter().map(|placement| ItemCategory::of(&placement.item)).min();
+        let fill = category.map_or("none", ItemCategory::color);
+        let items = placements.iter().map(|placement| {
+            placement.spoiler.as_ref().map_or_else(|| placement.item.to_string(), |spoiler| spoiler.item.clone())
+        }).collect::<Vec<_>>();
+        let title = if items.is_empty() { node.identifier().to_string() } else { format!("{}: {}", node.identifier(), items.join(", ")) };
+
+        let (x, y) = point(position);
+        writeln!(svg, "<circle cx=\"{}\" cy=\"{}\" r=\"8\" fill=\"{}\"><title>{}</title></circle>", x, y, fill, escape(&title)).unwrap();
+    }
+    svg += "</g>\n";
+
+    let spawn_name = seed_file.spawn.as_ref().map_or(DEFAULT_SPAWN, |spawn| &spawn.identifier[..]);
+    let (x, y) = point(&spawn);
+    writeln!(svg, "<g id=\"spawn\"><circle cx=\"{}\" cy=\"{}\" r=\"16\" fill=\"none\" stroke=\"#ffffff\" stroke-width=\"4\"/><text x=\"{}\" y=\"{}\" fill=\"#ffffff\" font-size=\"24\">Spawn</text><title>{}</title></g>", x, y, x + 20, y - 20, escape(spawn_name)).unwrap();
+
+    svg += "<g font-size=\"24\" font-family=\"sans-serif\">\n";
+    for (index, category) in CATEGORIES.iter().enumerate() {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+        let y = min_y - MARGIN + 40 + index as i32 * 32;
+        writeln!(svg, "<circle cx=\"{}\" cy=\"{}\" r=\"10\" fill=\"{}\"/><text x=\"{}\" y=\"{}\" fill=\"#ffffff\">{:?}</text>", min_x, y, category.color(), min_x + 20, y + 8, category).unwrap();
+    }
+    svg += "</g>\n</svg>\n";
+
+    Ok(svg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use crate::lexer;
+    use crate::util::{Pathsets, settings::Settings};
+
+    #[test]
+    fn svg_map() {
+        let graph = lexer::parse_logic(&PathBuf::from("areas.wotw"), &PathBuf::from("loc_data.csv"), &PathBuf::from("state_data.csv"), &Pathsets::default(), false).unwrap();
+        let seed_file = &crate::generate_seed(&graph, &Settings::default(), &[], Some(String::from("map"))).unwrap()[0];
+
+        let svg = render_svg(seed_file, &graph, false).unwrap();
+        let locations = graph.nodes.iter().filter(|node| node.can_place() && map_position(node).is_some()).count();
+        assert_eq!(svg.matches("r=\"8\"").count(), locations);
+        assert!(svg.contains("<title>MarshSpawn.Main</title>"));
+        assert!(svg.contains(ItemCategory::Skill.color()));
+        assert!(!svg.contains("<line"));
+
+        let svg = render_svg(seed_file, &graph, true).unwrap();
+        assert!(svg.contains("<line"));
+    }
+}
@@ -0,0 +1,260 @@
+use std::fmt::Write;
+
+use rustc_hash::FxHashMap;
+
+use super::{SeedFile, parse_placement};
+use crate::world::{
+    World,
+    graph::{Graph, Node},
+};
+use crate::util::{
+    settings::{Settings, Spawn},
+    uberstate::UberState,
+    constants::DEFAULT_SPAWN,
+};
+
+/// One row of a rendered spoiler
+#[derive(Debug, PartialEq)]
+pub struct SpoilerRow {
+    /// how many rounds of collecting everything in reach it takes to get to the location, None if it never gets reached
+    pub sphere: Option<usize>,
+    pub zone: String,
+    pub location: String,
+    pub item: String,
+}
+
+/// Finds out in which sphere each placement of the seed becomes reachable
+///
+/// Everything reachable from spawn is sphere 0, everything the items from sphere 0 unlock is sphere 1, and so on
+pub fn spheres(seed_file: &SeedFile, graph: &Graph, settings: &Settings) -> Result<FxHashMap<UberState, usize>, String> {
+    let mut world = World::new(graph);
+    world.player.spawn(settings);
+
+    let header_placements = seed_file.headers.iter().filter_map(|line| parse_placement(line).ok());
+    for placement in seed_file.placements.iter().cloned().chain(header_placements) {
+        world.preplace(placement.uber_state, placement.item);
+    }
+
+    let spawn = seed_file.spawn.as_ref().map_or(DEFAULT_SPAWN, |spawn| &spawn.identifier[..]);
+    let spawn = graph.find_spawn(spawn)?;
+
+    let mut spheres = FxHashMap::default();
+    spheres.insert(UberState::spawn(), 0);
+    world.collect_preplacements(&UberState::spawn());
+
+    let mut sphere = 0;
+    loop {
+        let reached = graph.reached_locations(&world.player, spawn, &world.uber_states)?;
+        let newly_reached = reached.iter()
+            .filter_map(|node| node.uber_state())
+            .filter(|uber_state| !spheres.contains_key(*uber_state))
+            .cloned()
+            .collect::<Vec<_>>();
+        if newly_reached.is_empty() { break; }
+
+        // collect only after everything in this sphere is known, otherwise the items would count towards the same sphere
+        for uber_state in &newly_reached {
+            spheres.insert(uber_state.clone(), sphere);
+        }
+        for uber_state in &newly_reached {
+            world.collect_preplacements(uber_state);
+        }
+
+        sphere += 1;
+    }
+
+    Ok(spheres)
+}
+
+/// Collects the seed's placements as rows sorted by zone, then sphere
+pub fn spoiler_rows(seed_file: &SeedFile, graph: &Graph, settings: &Settings) -> Result<Vec<SpoilerRow>, String> {
+    let spheres = spheres(seed_file, graph, settings)?;
+
+    let mut rows = seed_file.placements.iter().map(|placement| {
+        let node = graph.nodes.iter().find(|node| node.can_place() && node.uber_state() == Some(&placement.uber_state));
+        let spoiler = placement.spoiler.as_ref();
+
+        let (zone, location) = if placement.uber_state == UberState::spawn() {
+            (String::from("Spawn"), String::from("Spawn"))
+        } else {
+            let zone = spoiler.and_then(|spoiler| spoiler.zone.clone())
+                .or_else(|| node.and_then(Node::zone).map(str::to_string))
+                .unwrap_or_else(|| String::from("Other"));
+            (zone, node.map_or_else(|| placement.uber_state.to_string(), |node| node.identifier().to_string()))
+        };
+        let item = spoiler.map_or_else(|| placement.item.to_string(), |spoiler| spoiler.item.clone());
+
+        SpoilerRow {
+            sphere: spheres.get(&placement.uber_state).copied(),
+            zone,
+            location,
+            item,
+        }
+    }).collect::<Vec<_>>();
+
+    // Spawn first, Other last and unreached placements at the end of their zone
+    rows.sort_by(|a, b| {
+        let zone_order = |zone: &str| match zone { "Spawn" => 0, "Other" => 2, _ => 1 };
+        zone_order(&a.zone).cmp(&zone_order(&b.zone))
+            .then_with(|| a.zone.cmp(&b.zone))
+            .then_with(|| a.sphere.unwrap_or(usize::MAX).cmp(&b.sphere.unwrap_or(usize::MAX)))
+            .then_with(|| a.location.cmp(&b.location))
+    });
+
+    Ok(rows)
+}
+
+fn settings_summary(seed_file: &SeedFile) -> Vec<(&'static str, String)> {
+    let mut summary = Vec::new();
+
+    if let Some(seed) = &seed_file.seed {
+        summary.push(("Seed", seed.clone()));
+    }
+    summary.push(("Spawn", seed_file.spawn.as_ref().map_or_else(|| DEFAULT_SPAWN.to_string(), |spawn| spawn.identifier.clone())));
+    if !seed_file.flags.is_empty() {
+        summary.push(("Flags", seed_file.flags.join(", ")));
+    }
+
+    if let Some(settings) = &seed_file.config {
+        let mut pathsets = settings.pathsets.pathsets.iter().map(|pathset| format!("{:?}", pathset)).collect::<Vec<_>>();
+        pathsets.extend(settings.pathsets.declared.iter().cloned());
+        pathsets.sort();
+        summary.push(("Logic", pathsets.join(", ")));
+
+        if settings.worlds > 1 {
+            summary.push(("Worlds", settings.worlds.to_string()));
+        }
+        if !settings.players.is_empty() {
+            summary.push(("Players", settings.players.join(", ")));
+        }
+        summary.push(("Spawn setting", match &settings.spawn_loc {
+            Spawn::Set(spawn) => spawn.clone(),
+            Spawn::Random => String::from("Random"),
+            Spawn::FullyRandom => String::from("Fully random"),
+        }));
+        if settings.hard {
+            summary.push(("Difficulty", String::from("Hard")));
+        }
+        if !settings.header_list.is_empty() {
+            summary.push(("Headers", settings.header_list.iter().map(|header| header.display().to_string()).collect::<Vec<_>>().join(", ")));
+        }
+    }
+
+    summary
+}
+
+fn sphere_name(sphere: Option<usize>) -> String {
+    sphere.map_or_else(|| String::from("unreached"), |sphere| sphere.to_string())
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+pub fn render_markdown(seed_file: &SeedFile, rows: &[SpoilerRow]) -> String {
+    let mut markdown = String::from("# Spoiler\n\n");
+
+    for (setting, value) in settings_summary(seed_file) {
+        writeln!(markdown, "- **{}:** {}", setting, escape_markdown(&value)).unwrap();
+    }
+
+    let mut zone = None;
+    for row in rows {
+        if zone != Some(&row.zone) {
+            zone = Some(&row.zone);
+            write!(markdown, "\n## {}\n\n| Sphere | Location | Item |\n| --- | --- | --- |\n", row.zone).unwrap();
+        }
+        writeln!(markdown, "| {} | {} | {} |", sphere_name(row.sphere), escape_markdown(&row.location), escape_markdown(&row.item)).unwrap();
+    }
+
+    markdown
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const HTML_STYLE: &str = "body { font-family: sans-serif; margin: 2em; }\n\
+    table { border-collapse: collapse; width: 100%; }\n\
+    th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }\n\
+    tr.zone th { background: #eee; }\n\
+    #search { width: 100%; padding: 0.5em; margin-bottom: 1em; }";
+
+// Hides every placement row that doesn't contain the search text, and zone headings without visible rows
+const HTML_SCRIPT: &str = "const search = document.getElementById('search');\n\
+    search.addEventListener('input', () => {\n\
+    const text = search.value.toLowerCase();\n\
+    let heading = null, visible = false;\n\
+    for (const row of document.querySelectorAll('tbody tr')) {\n\
+    if (row.classList.contains('zone')) { if (heading) heading.hidden = !visible; heading = row; visible = false; continue; }\n\
+    row.hidden = !row.textContent.toLowerCase().includes(text);\n\
+    visible = visible || !row.hidden;\n\
+    }\n\
+    if (heading) heading.hidden = !visible;\n\
+    });";
+
+pub fn render_html(seed_file: &SeedFile, rows: &[SpoilerRow]) -> String {
+    let title = seed_file.seed.as_ref().map_or_else(|| String::from("Spoiler"), |seed| format!("Spoiler for {}", escape_html(seed)));
+
+    let mut html = format!("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n<h1>{}</h1>\n", title, HTML_STYLE, title);
+
+    html += "<dl>\n";
+    for (setting, value) in settings_summary(seed_file) {
+        writeln!(html, "<dt>{}</dt><dd>{}</dd>", setting, escape_html(&value)).unwrap();
+    }
+    html += "</dl>\n";
+
+    html += "<input id=\"search\" type=\"search\" placeholder=\"Search locations and items\">\n";
+    html += "<table>\n<thead><tr><th>Sphere</th><th>Location</th><th>Item</th></tr></thead>\n<tbody>\n";
+
+    let mut zone = None;
+    for row in rows {
+        if zone != Some(&row.zone) {
+            zone = Some(&row.zone);
+            writeln!(html, "<tr class=\"zone\"><th colspan=\"3\">{}</th></tr>", escape_html(&row.zone)).unwrap();
+        }
+        writeln!(html, "<tr><td>{}</td><td>{}</td><td>{}</td></tr>", sphere_name(row.sphere), escape_html(&row.location), escape_html(&row.item)).unwrap();
+    }
+
+    write!(html, "</tbody>\n</table>\n<script>\n{}\n</script>\n</body>\n</html>\n", HTML_SCRIPT).unwrap();
+
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use crate::lexer;
+    use crate::util::Pathsets;
+
+    #[test]
+    fn spoiler_render() {
+        let graph = lexer::parse_logic(&PathBuf::from("areas.wotw"), &PathBuf::from("loc_data.csv"), &PathBuf::from("state_data.csv"), &Pathsets::default(), false).unwrap();
+        let settings = Settings::default();
+        let seed_file = &crate::generate_seed(&graph, &settings, &[String::from("!!name 2|100 Sharp Stick")], Some(String::from("render"))).unwrap()[0];
+
+        let rows = spoiler_rows(seed_file, &graph, &settings).unwrap();
+        assert_eq!(rows.len(), seed_file.placements.len());
+        assert_eq!(rows[0].zone, "Spawn");
+        assert!(rows.iter().any(|row| row.item == "Sharp Stick"));
+        assert!(rows.iter().any(|row| row.sphere == Some(0)));
+        assert!(rows.iter().all(|row| row.sphere.is_some()), "every placement in a generated seed should be reachable");
+        for pair in rows.windows(2) {
+            if pair[0].zone == pair[1].zone {
+                assert!(pair[0].sphere <= pair[1].sphere);
+            }
+        }
+
+        let markdown = render_markdown(seed_file, &rows);
+        assert!(markdown.contains("- **Seed:** render"));
+        assert!(markdown.contains("| Sharp Stick |"));
+
+        let html = render_html(seed_file, &rows);
+        assert!(html.contains("<title>Spoiler for render</title>"));
+        assert!(html.contains("<td>Sharp Stick</td>"));
+    }
+}
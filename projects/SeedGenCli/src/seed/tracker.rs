@@ -0,0 +1,252 @@
+use rustc_hash::FxHashMap;
+use serde::Serialize;
+
+use super::{SeedFile, parse_placement};
+use crate::inventory::Inventory;
+use crate::world::{
+    World,
+    graph::{Graph, Node},
+    player::Player,
+};
+use crate::util::{
+    Position, NodeType,
+    settings::Settings,
+    uberstate::{UberState, UberIdentifier, UberValue},
+};
+
+/// Reads an uber state dump from the game, with one "group, id, value" line per uber state
+pub fn parse_dump(dump: &str) -> Result<FxHashMap<UberIdentifier, UberValue>, String> {
+    let mut uber_states = FxHashMap::default();
+
+    for (index, line) in dump.lines().enumerate() {
+        let line = line.split("//").next().unwrap().trim();
+        if line.is_empty() { continue; }
+
+        let mut parts = line.split(|char: char| char == ',' || char == '|' || char == '=' || char.is_whitespace()).filter(|part| !part.is_empty());
+        let mut next = || parts.next().ok_or_else(|| format!("expected group, id and value in line {}", index + 1));
+        let identifier = UberIdentifier::from_parts(next()?, next()?).map_err(|err| format!("{} in line {}", err, index + 1))?;
+        let value = next()?;
+
+        let value = if let Ok(value) = value.parse::<i32>() {
+            UberValue::Int(value)
+        } else if let Ok(value) = value.parse::<f32>() {
+            UberValue::Float(value)
+        } else if let Ok(value) = value.parse::<bool>() {
+            UberValue::Bool(value)
+        } else {
+            return Err(format!("invalid value {} in line {}", value, index + 1));
+        };
+
+        uber_states.insert(identifier, value);
+    }
+
+    Ok(uber_states)
+}
+
+/// Whether the dump shows the location as collected
+pub fn is_visited(uber_state: &UberState, dump: &FxHashMap<UberIdentifier, UberValue>) -> bool {
+    dump.get(&uber_state.identifier).is_some_and(|value| uber_state.is_met(value))
+}
+
+/// Sets up a world with the inventory the player has in the dump, by collecting the seed's items on every visited location
+///
+/// Spent spirit light can't be known from the seed, so it assumes everything collected is still there
+pub fn tracked_world<'a>(seed_file: &SeedFile, graph: &'a Graph, settings: &Settings, dump: &FxHashMap<UberIdentifier, UberValue>) -> World<'a> {
+    let mut world = World::new(graph);
+    world.player.spawn(settings);
+
+    let header_placements = seed_file.headers.iter().filter_map(|line| parse_placement(line).ok());
+    for placement in seed_file.placements.iter().cloned().chain(header_placements) {
+        world.preplace(placement.uber_state, placement.item);
+    }
+
+    world.collect_preplacements(&UberState::spawn());
+    let mut visited = world.preplacements.keys().filter(|uber_state| is_visited(uber_state, dump)).cloned().collect::<Vec<_>>();
+    visited.sort();
+    for uber_state in visited {
+        world.collect_preplacements(&uber_state);
+    }
+
+    world.uber_states.extend(dump.iter().map(|(identifier, value)| (identifier.clone(), *value)));
+
+    world
+}
+
+/// Finds the itemsets that would let the player progress, together with how many new locations each of them unlocks
+///
+/// Sets that unlock more come first
+pub fn progressions(world: &World, spawn: &Node) -> Result<Vec<(Inventory, usize)>, String> {
+    let player = &world.player;
+    let (reached, unmet) = world.graph.reached_and_progressions(player, spawn, &world.uber_states)?;
+    let owned_states = reached.iter().filter(|&&node| !node.can_place()).map(|&node| node.index()).collect::<Vec<_>>();
+
+    let mut itemsets: Vec<Inventory> = Vec::new();
+    for (requirement, best_orbs) in &unmet {
+        for (mut needed, orb_cost) in requirement.items_needed(player, &owned_states) {
+            player.missing_items(&mut needed);
+
+            for orbs in best_orbs {
+                let missing = Player::missing_for_orbs(&needed, orb_cost, *orbs);
+                if missing.inventory.is_empty() || itemsets.contains(&missing) { continue; }
+                itemsets.push(missing);
+            }
+        }
+    }
+
+    // a set that contains another one doesn't tell anything new
+    let itemsets = itemsets.iter()
+        .filter(|&itemset| !itemsets.iter().any(|other| other != itemset && itemset.contains(other)))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let reached = reached.iter().filter(|node| node.can_place()).map(|node| node.index()).collect::<Vec<_>>();
+    let mut progressions = itemsets.into_iter().map(|itemset| {
+        let lookahead_player = Player {
+            inventory: player.inventory.merge(&itemset),
+            ..player.clone()
+        };
+        let lookahead_reached = world.graph.reached_locations(&lookahead_player, spawn, &world.uber_states)?;
+        let unlocked = lookahead_reached.iter().filter(|node| node.can_place() && !reached.contains(&node.index())).count();
+
+        Ok((itemset, unlocked))
+    }).collect::<Result<Vec<_>, String>>()?;
+
+    progressions.sort_by(|(a, a_unlocked), (b, b_unlocked)| b_unlocked.cmp(a_unlocked).then_with(|| a.item_count().cmp(&b.item_count())));
+
+    Ok(progressions)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonReach<'a> {
+    locations: Vec<JsonLocation<'a>>,
+    states: Vec<&'a str>,
+    quests: Vec<&'a str>,
+    max_health: f32,
+    max_energy: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    progressions: Option<Vec<JsonProgression>>,
+}
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonLocation<'a> {
+    name: &'a str,
+    zone: Option<&'a str>,
+    position: Option<&'a Position>,
+    uber_state: String,
+}
+#[derive(Serialize)]
+struct JsonProgression {
+    items: Vec<JsonItem>,
+    unlocks: usize,
+}
+#[derive(Serialize)]
+struct JsonItem {
+    item: String,
+    amount: u16,
+}
+
+/// Writes the result of a reach check as json, including every reached node and the player's orbs
+pub fn write_reach_json(player: &Player, reached: &[&Node], progressions: Option<&[(Inventory, usize)]>) -> Result<String, String> {
+    let locations = reached.iter()
+        .filter(|node| node.can_place())
+        .map(|node| JsonLocation {
+            name: node.identifier(),
+            zone: node.zone().filter(|zone| !zone.is_empty()),
+            position: node.position(),
+            uber_state: node.uber_state().map_or_else(String::new, UberState::to_string),
+        })
+        .collect();
+    let identifiers = |node_type: NodeType| reached.iter().filter(|node| node.node_type() == node_type).map(|node| node.identifier()).collect();
+
+    let progressions = progressions.map(|progressions| progressions.iter().map(|(itemset, unlocks)| {
+        let mut items = itemset.inventory.iter().map(|(item, amount)| JsonItem { item: item.to_string(), amount: *amount }).collect::<Vec<_>>();
+        items.sort_by(|a, b| a.item.cmp(&b.item));
+        JsonProgression { items, unlocks: *unlocks }
+    }).collect());
+
+    let json = JsonReach {
+        locations,
+        states: identifiers(NodeType::State),
+        quests: identifiers(NodeType::Quest),
+        max_health: player.max_health(),
+        max_energy: player.max_energy(),
+        progressions,
+    };
+
+    serde_json::to_string_pretty(&json).map_err(|err| format!("Failed to write reach check as json: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use crate::lexer;
+    use crate::inventory::Item;
+    use crate::util::{Skill, Resource, Pathsets};
+
+    #[test]
+    fn dump_tracking() {
+        let dump = parse_dump("21786, 60210, 1\n// comment\n\n48248|51645=2\n21786 49485 true\n").unwrap();
+        assert_eq!(dump.len(), 3);
+        assert_eq!(dump[&UberIdentifier::from_parts("48248", "51645").unwrap()], UberValue::Int(2));
+        assert!(parse_dump("21786, 60210").is_err());
+        assert!(parse_dump("21786, 60210, yes").is_err());
+
+        let seed_file = SeedFile::parse("\n3|0|2|100\n21786|60210|2|8\n21786|25095|2|102\n48248|51645=3|1|0\n48248|51645=1|1|0\n").unwrap();
+        let graph = Graph::default();
+        let world = tracked_world(&seed_file, &graph, &Settings::default(), &dump);
+        let inventory = &world.player.inventory;
+        assert!(inventory.has(&Item::Skill(Skill::Sword), 1));
+        assert!(inventory.has(&Item::Skill(Skill::Launch), 1));
+        assert!(!inventory.has(&Item::Skill(Skill::Dash), 1));
+        assert_eq!(inventory.get(&Item::Resource(Resource::Health)), 7);
+    }
+
+    #[test]
+    fn unlocking_progressions() {
+        let graph = &lexer::parse_logic(&PathBuf::from("areas.wotw"), &PathBuf::from("loc_data.csv"), &PathBuf::from("state_data.csv"), &Pathsets::default(), false).unwrap();
+        let mut world = World::new(graph);
+        world.player.spawn(&Settings::default());
+        let spawn = graph.find_spawn("MarshSpawn.Main").unwrap();
+
+        let progressions = progressions(&world, spawn).unwrap();
+        assert!(!progressions.is_empty());
+        assert!(progressions[0].1 > 0);
+        assert!(progressions.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        for (itemset, _) in &progressions {
+            assert!(!itemset.inventory.is_empty());
+            assert!(!progressions.iter().any(|(other, _)| other != itemset && itemset.contains(other)));
+        }
+
+        let (best, unlocked) = &progressions[0];
+        let before = graph.reached_locations(&world.player, spawn, &world.uber_states).unwrap().iter().filter(|node| node.can_place()).count();
+        world.player.inventory = world.player.inventory.merge(best);
+        let after = graph.reached_locations(&world.player, spawn, &world.uber_states).unwrap().iter().filter(|node| node.can_place()).count();
+        assert_eq!(after, before + unlocked);
+    }
+
+    #[test]
+    fn reach_json() {
+        let graph = &lexer::parse_logic(&PathBuf::from("areas.wotw"), &PathBuf::from("loc_data.csv"), &PathBuf::from("state_data.csv"), &Pathsets::default(), false).unwrap();
+        let mut world = World::new(graph);
+        world.player.spawn(&Settings::default());
+        world.player.inventory.grant(Item::Skill(Skill::DoubleJump), 1);
+        let spawn = graph.find_spawn("MarshSpawn.Main").unwrap();
+        let reached = graph.reached_locations(&world.player, spawn, &world.uber_states).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&write_reach_json(&world.player, &reached, None).unwrap()).unwrap();
+        let locations = json["locations"].as_array().unwrap();
+        assert_eq!(locations.len(), reached.iter().filter(|node| node.can_place()).count());
+        assert!(locations.iter().all(|location| location["uberState"].is_string() && location["name"].is_string()));
+        assert!(json["states"].as_array().is_some());
+        assert_eq!(json["maxHealth"], world.player.max_health());
+        assert_eq!(json["maxEnergy"], world.player.max_energy());
+        assert!(json.get("progressions").is_none());
+
+        let progressions = progressions(&world, spawn).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&write_reach_json(&world.player, &reached, Some(&progressions)).unwrap()).unwrap();
+        assert_eq!(json["progressions"].as_array().unwrap().len(), progressions.len());
+        assert!(json["progressions"][0]["items"][0]["amount"].is_u64());
+    }
+}
@@ -2,6 +2,7 @@ pub mod settings;
 pub mod orbs;
 pub mod uberstate;
 pub mod constants;
+pub mod spoiler;
 
 use std::{
     fmt,
@@ -15,9 +16,12 @@ use serde::{Serialize, Deserialize};
 
 use uberstate::{UberState, UberIdentifier};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub struct Pathsets {
     pub pathsets: FxHashSet<Pathset>,
+    // names of pathsets declared in the logic files
+    #[serde(default, rename = "declaredPathsets", skip_serializing_if = "FxHashSet::is_empty")]
+    pub declared: FxHashSet<String>,
 }
 impl Default for Pathsets {
     fn default() -> Pathsets {
@@ -26,10 +30,39 @@ impl Default for Pathsets {
 
         Pathsets {
             pathsets,
+            declared: FxHashSet::default(),
         }
     }
 }
 impl Pathsets {
+    /// Reads the pathset names used on the command line
+    ///
+    /// Names that aren't built in are kept as declared pathsets, to be checked against the logic once it's parsed
+    pub fn from_names(names: &[String]) -> Pathsets {
+        let mut pathsets = Pathsets::default();
+
+        for pathset in names {
+            match &pathset[..] {
+                "mo" | "moki" => {},
+                "go" | "gorlek" => pathsets.add(Pathset::Gorlek),
+                "un" | "unsafe" => pathsets.add(Pathset::Unsafe),
+                "gl" | "glitch" => pathsets.add_glitches(),
+                "shurikenbreak" => pathsets.add(Pathset::ShurikenBreak),
+                "sjump" | "sentryjump" => {
+                    pathsets.add(Pathset::SwordSentryJump);
+                    pathsets.add(Pathset::HammerSentryJump);
+                },
+                "swordsjump" | "swordsentryjump" => pathsets.add(Pathset::SwordSentryJump),
+                "hammersjump" | "hammersentryjump" => pathsets.add(Pathset::HammerSentryJump),
+                "sentryburn" => pathsets.add(Pathset::SentryBurn),
+                "removekillplane" => pathsets.add(Pathset::RemoveKillPlane),
+                other => pathsets.declare(other),
+            }
+        }
+
+        pathsets
+    }
+
     pub fn add(&mut self, pathset: Pathset) {
         match pathset {
             Pathset::Unsafe => {
@@ -50,6 +83,39 @@ impl Pathsets {
     pub fn contains(&self, pathset: Pathset) -> bool {
         self.pathsets.contains(&pathset)
     }
+
+    pub fn declare(&mut self, name: &str) {
+        self.declared.insert(name.to_string());
+    }
+    pub fn contains_declared(&self, name: &str) -> bool {
+        self.declared.contains(name)
+    }
+    /// Matches the declared pathsets against the ones the logic declares and adds their parents
+    ///
+    /// Names are compared with [`normalize_name`], afterwards they are spelled the same way as in the logic
+    pub fn resolve(&mut self, declarations: &[DeclaredPathset]) -> Result<(), String> {
+        let mut unresolved = self.declared.drain().collect::<Vec<_>>();
+        while let Some(name) = unresolved.pop() {
+            let normalized = normalize_name(&name);
+            let declaration = declarations.iter().find(|declaration| normalize_name(&declaration.name) == normalized).ok_or_else(|| {
+                if declarations.is_empty() {
+                    return format!("Unknown pathset {}, the logic doesn't declare any pathsets", name);
+                }
+                let known = declarations.iter().map(|declaration| &declaration.name[..]).collect::<Vec<_>>();
+                format!("Unknown pathset {}, the pathsets declared in the logic are: {}", name, known.join(", "))
+            })?;
+            if !self.declared.insert(declaration.name.clone()) {
+                continue;
+            }
+            if let Some(parent) = &declaration.parent {
+                match Pathset::from_difficulty(parent) {
+                    Some(pathset) => self.add(pathset),
+                    None => unresolved.push(parent.clone()),
+                }
+            }
+        }
+        Ok(())
+    }
 }
 impl<P> From<P> for Pathsets
 where P: IntoIterator<Item=Pathset>
@@ -74,8 +140,29 @@ pub enum Pathset {
     SentryBurn,         // Melting Ice using Sentries
     RemoveKillPlane,    // Removing Shriek's Killplane at Feeding Grounds
 }
+impl Pathset {
+    /// The built-in difficulties, which declared pathsets may use as their parent
+    pub fn from_difficulty(name: &str) -> Option<Pathset> {
+        match name {
+            "moki" => Some(Pathset::Moki),
+            "gorlek" => Some(Pathset::Gorlek),
+            "unsafe" => Some(Pathset::Unsafe),
+            _ => None,
+        }
+    }
+}
+
+/// A pathset declared in the logic files, on top of the built-in ones
+///
+/// Enabling it enables its parent as well
+#[derive(Debug, Clone)]
+pub struct DeclaredPathset {
+    pub name: String,
+    pub parent: Option<String>,
+    pub description: String,
+}
 
-#[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum GoalMode {
     Wisps,
     Trees,
@@ -93,6 +180,15 @@ impl fmt::Display for GoalMode {
     }
 }
 
+/// Lowercases the name and drops anything but letters and digits, so "Double Jump" and "double_jump" both become "doublejump"
+pub fn normalize_name(name: &str) -> String {
+    name.chars().filter(char::is_ascii_alphanumeric).map(|char| char.to_ascii_lowercase()).collect()
+}
+// Finds the variant whose name matches, trying every id
+fn find_by_name<T: fmt::Debug>(name: &str, from_id: fn(u8) -> Option<T>) -> Option<T> {
+    (0..=u8::MAX).filter_map(from_id).find(|variant| normalize_name(&format!("{:?}", variant)) == name)
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Skill {
     Bash,
@@ -148,6 +244,18 @@ impl Skill {
             _ => None,
         }
     }
+    /// Accepts the skill names case-insensitively, as well as the ingame names of the weapons
+    pub fn from_name(name: &str) -> Option<Skill> {
+        let name = normalize_name(name);
+        match &name[..] {
+            "spiritedge" | "edge" => Some(Skill::Sword),
+            "spiritsmash" | "smash" => Some(Skill::Hammer),
+            "spiritstar" | "star" => Some(Skill::Shuriken),
+            "spike" | "spiritspike" => Some(Skill::Spear),
+            "dj" => Some(Skill::DoubleJump),
+            _ => find_by_name(&name, Skill::from_id),
+        }
+    }
     pub fn to_id(self) -> u16 {
         match self {
             Skill::Bash => 0,
@@ -232,6 +340,17 @@ impl Resource {
             _ => None,
         }
     }
+    pub fn from_name(name: &str) -> Option<Resource> {
+        let name = normalize_name(name);
+        match &name[..] {
+            "life" | "healthfragment" | "lifefragment" => Some(Resource::Health),
+            "energyfragment" => Some(Resource::Energy),
+            "gorlekore" => Some(Resource::Ore),
+            "key" => Some(Resource::Keystone),
+            "slot" => Some(Resource::ShardSlot),
+            _ => find_by_name(&name, Resource::from_id),
+        }
+    }
     pub fn to_id(self) -> u16 {
         match self {
             Resource::Health => 0,
@@ -313,6 +432,13 @@ impl Shard {
             _ => None,
         }
     }
+    pub fn from_name(name: &str) -> Option<Shard> {
+        let name = normalize_name(name);
+        match &name[..] {
+            "lightharvest" => Some(Shard::SpiritLightHarvest),
+            _ => find_by_name(&name, Shard::from_id),
+        }
+    }
     pub fn to_id(self) -> u16 {
         match self {
             Shard::Overcharge => 1,
@@ -394,6 +520,17 @@ impl Teleporter {
             _ => None,
         }
     }
+    /// Accepts the teleporter names with or without a "TP" or "Teleporter" suffix
+    pub fn from_name(name: &str) -> Option<Teleporter> {
+        let name = normalize_name(name);
+        let name = name.strip_suffix("teleporter").or_else(|| name.strip_suffix("tp")).unwrap_or(&name);
+        match name {
+            "wellspringglades" => Some(Teleporter::Glades),
+            "inkwatermarsh" => Some(Teleporter::Marsh),
+            "kwolokshollow" => Some(Teleporter::Hollow),
+            _ => find_by_name(name, Teleporter::from_id),
+        }
+    }
     pub fn to_id(self) -> u16 {
         match self {
             Teleporter::Burrows => 0,
@@ -745,7 +882,7 @@ pub enum NodeType {
     Quest,
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize)]
 pub struct Position {
     pub x: i16,
     pub y: i16,
@@ -284,3 +284,14 @@ pub const SHOP_PRICES: &[(&str, UberIdentifier, UberIdentifier)] = &[
         }
     ),
 ];
+pub const SEED_HASH_LENGTH: usize = 4;  // How many words to use when displaying the seed hash
+pub const SEED_HASH_WORDS: &[&str] = &[
+    "Ori", "Ku", "Seir", "Kwolok", "Shriek", "Mora", "Howl", "Baur",
+    "Tokk", "Lupo", "Grom", "Opher", "Twillen", "Motay", "Tuley", "Hand",
+    "Moki", "Gorlek", "Luma", "Kii", "Weeler", "Mokk", "Tatsu", "Beetle",
+    "Glades", "Marsh", "Hollow", "Wellspring", "Burrows", "Pools", "Reach", "Depths",
+    "Woods", "Wastes", "Ruins", "Willow", "Bash", "Launch", "Grapple", "Flap",
+    "Dash", "Burrow", "Flash", "Sword", "Hammer", "Bow", "Spear", "Blaze",
+    "Sentry", "Shuriken", "Regenerate", "Keystone", "Ore", "Wisp", "Spirit", "Light",
+    "Lantern", "Lever", "Shrine", "Seedling", "Petal", "Nibel", "Niwen", "Sein",
+];
@@ -22,6 +22,10 @@ impl AddAssign for Orbs {
     }
 }
 
+fn is_worse(orbs: &Orbs, other: &Orbs) -> bool {
+    other.energy > orbs.energy && other.health >= orbs.health || other.energy >= orbs.energy && other.health > orbs.health
+}
+
 pub fn either(a: &[Orbs], b: &[Orbs]) -> SmallVec<[Orbs; 3]> {
     if b.is_empty() || a.is_empty() {
         smallvec![Orbs::default()]
@@ -75,9 +79,7 @@ pub fn both(a: &[Orbs], b: &[Orbs]) -> SmallVec<[Orbs; 3]> {
                 }
             }
         }
-        product.clone().into_iter().filter(|orbs| {
-            !product.iter().any(|other| other.energy > orbs.energy && other.health >= orbs.health || other.energy >= orbs.energy && other.health > orbs.health)
-        }).collect()
+        product.clone().into_iter().filter(|orbs| !product.iter().any(|other| is_worse(orbs, other))).collect()
     }
 }
 pub fn both_single(a: &[Orbs], b: Orbs) -> SmallVec<[Orbs; 3]> {
@@ -91,10 +93,23 @@ pub fn both_single(a: &[Orbs], b: Orbs) -> SmallVec<[Orbs; 3]> {
                 product.push(orbs);
             }
         }
-        product.clone().into_iter().filter(|orbs| {
-            !product.iter().any(|other| other.energy > orbs.energy && other.health >= orbs.health || other.energy >= orbs.energy && other.health > orbs.health)
-        }).collect()
+        product.clone().into_iter().filter(|orbs| !product.iter().any(|other| is_worse(orbs, other))).collect()
+    }
+}
+
+/// Whether any of the new orbs isn't already covered by at least as many health and energy in the prior ones
+pub fn improves(prior: &[Orbs], new: &[Orbs]) -> bool {
+    new.iter().any(|new| !prior.iter().any(|prior| prior.energy >= new.energy && prior.health >= new.health))
+}
+/// Combines two sets of alternative orbs, leaving out any that are worse than another one
+pub fn merge(a: &[Orbs], b: &[Orbs]) -> SmallVec<[Orbs; 3]> {
+    let mut merged = SmallVec::<[Orbs; 3]>::with_capacity(a.len() + b.len());
+    for orbs in a.iter().chain(b) {
+        if !merged.contains(orbs) {
+            merged.push(*orbs);
+        }
     }
+    merged.clone().into_iter().filter(|orbs| !merged.iter().any(|other| is_worse(orbs, other))).collect()
 }
 
 #[cfg(test)]
@@ -127,5 +142,13 @@ mod tests {
         assert_eq!(either(&b, &a), either_orbs);
         assert_eq!(both(&a, &b), both_orbs);
         assert_eq!(both(&b, &a), both_orbs);
+
+        let a: SmallVec<[_; 3]> = smallvec![Orbs { health: 30.0, energy: 1.0 }, Orbs { health: 10.0, energy: 3.0 }];
+        assert!(!improves(&a, &[Orbs { health: 20.0, energy: 1.0 }]));
+        assert!(!improves(&a, &a));
+        assert!(improves(&a, &[Orbs { health: 20.0, energy: 2.0 }]));
+        assert!(improves(&[], &a));
+        let merged: SmallVec<[_; 3]> = smallvec![Orbs { health: 30.0, energy: 1.0 }, Orbs { health: 20.0, energy: 3.0 }];
+        assert_eq!(merge(&a, &[Orbs { health: 20.0, energy: 3.0 }, Orbs { health: 5.0, energy: 1.0 }]), merged);
     }
 }
\ No newline at end of file
@@ -1,7 +1,8 @@
-use std::{io, path::{Path, PathBuf}};
+use std::{convert::TryFrom, path::{Path, PathBuf}};
 
 use rustc_hash::FxHashSet;
 use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value};
 
 use super::{Pathsets, Pathset, GoalMode, constants::DEFAULT_SPAWN};
 
@@ -40,6 +41,14 @@ struct OldSettings {
     pub spawn_loc: String,
     pub header_list: Vec<PathBuf>,
 }
+impl OldSettings {
+    fn fields() -> Vec<String> {
+        match serde_json::to_value(OldSettings::default()) {
+            Ok(Value::Object(object)) => object.into_iter().map(|(key, _)| key).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
 impl Default for OldSettings {
     fn default() -> OldSettings {
         OldSettings {
@@ -60,8 +69,11 @@ impl Default for OldSettings {
         }
     }
 }
-fn read_old(json: &str) -> Result<Settings, io::Error> {
-    let old_settings: OldSettings = serde_json::from_str(json)?;
+fn read_old(old_settings: Value) -> Result<Settings, serde_json::Error> {
+    let old_settings: OldSettings = serde_json::from_value(old_settings)?;
+
+    if old_settings.debug_info { log::warn!("The debugInfo setting has been removed and will be ignored"); }
+    if old_settings.output_folder != PathBuf::new() { log::warn!("The outputFolder setting has been removed and will be ignored"); }
 
     let mut pathsets = Pathsets::default();
     if old_settings.gorlek_paths { pathsets.add(Pathset::Gorlek); }
@@ -97,7 +109,7 @@ fn read_old(json: &str) -> Result<Settings, io::Error> {
     })
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum Spawn {
     Set(String),
     Random,
@@ -109,9 +121,41 @@ impl Default for Spawn {
     }
 }
 
-// TODO output folder?
-#[derive(Debug, Serialize, Deserialize)]
+// Bump this and add a migration when making changes to Settings that would break reading older settings
+const SCHEMA_VERSION: usize = 1;
+/// Each migration takes settings of the schema version matching its index to the next version
+const MIGRATIONS: [fn(Value) -> Result<Value, String>; SCHEMA_VERSION] = [
+    migrate_java_settings,
+];
+
+fn migrate_java_settings(settings: Value) -> Result<Value, String> {
+    if let Some(object) = settings.as_object() {
+        let known = OldSettings::fields();
+        warn_unknown_fields(object, &known);
+    }
+    let settings = read_old(settings).map_err(|err| format!("Failed to read legacy settings: {}", err))?;
+    serde_json::to_value(settings).map_err(|err| format!("Failed to migrate legacy settings: {}", err))
+}
+
+fn warn_unknown_fields(object: &Map<String, Value>, known: &[String]) {
+    for key in object.keys() {
+        if !known.contains(key) {
+            log::warn!("Unknown setting {} will be ignored", key);
+        }
+    }
+}
+
+#[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
+struct Versioned<'a> {
+    schema_version: usize,
+    #[serde(flatten)]
+    settings: &'a Settings,
+}
+
+// TODO output folder?
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct Settings {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
@@ -147,19 +191,54 @@ impl Settings {
         let mut settings = Settings::default();
         for line in seed.lines() {
             if let Some(config) = line.strip_prefix("// Config: ") {
-                settings = serde_json::from_str(&config).or_else(|err| {  // read directly or fall back to reading old settings
-                    read_old(&config).map_err(|_| format!("Failed to read settings: {}", err))
-                })?;
+                settings = Settings::parse(config)?;
             }
         }
         Ok(settings)
     }
-    pub fn from_preset(preset: &Path) -> Result<Settings, String> {
-        let content = super::read_file(preset, "presets")?;
-        serde_json::from_str(&content).map_err(|err| format!("Failed to read settings from {}: {}", preset.display(), err))
+    /// Reads serialized settings of any schema version, migrating them to the current one
+    pub fn parse(json: &str) -> Result<Settings, String> {
+        let mut settings: Value = serde_json::from_str(json).map_err(|err| format!("Failed to read settings: {}", err))?;
+        let object = settings.as_object().ok_or_else(|| String::from("Failed to read settings: expected an object"))?;
+
+        let version = match object.get("schemaVersion") {
+            Some(version) => version.as_u64().and_then(|version| usize::try_from(version).ok()).ok_or_else(|| format!("Invalid schema version {}", version))?,
+            None if object.contains_key("flags") => 0,  // written by the java-based seed generator
+            None => 1,  // written before settings carried a schema version
+        };
+        if version > SCHEMA_VERSION {
+            return Err(format!("These settings use schema version {}, but this version of the generator only supports up to {}. Please update the generator.", version, SCHEMA_VERSION));
+        }
+        for migration in &MIGRATIONS[version..] {
+            settings = migration(settings)?;
+        }
+
+        if let Some(object) = settings.as_object_mut() {
+            object.remove("schemaVersion");
+            warn_unknown_fields(object, &Settings::fields());
+        }
+
+        serde_json::from_value(settings).map_err(|err| format!("Failed to read settings: {}", err))
     }
     pub fn write(settings: &Settings) -> Result<String, String> {
-        serde_json::to_string(settings).map_err(|err| format!("Invalid Settings: {}", err))
+        let versioned = Versioned { schema_version: SCHEMA_VERSION, settings };
+        serde_json::to_string(&versioned).map_err(|err| format!("Invalid Settings: {}", err))
+    }
+    /// Same as write, for embedding the settings into other json documents
+    pub fn write_value(settings: &Settings) -> Result<Value, String> {
+        let versioned = Versioned { schema_version: SCHEMA_VERSION, settings };
+        serde_json::to_value(&versioned).map_err(|err| format!("Invalid Settings: {}", err))
+    }
+    fn fields() -> Vec<String> {
+        let mut settings = Settings {
+            version: Some(String::new()),
+            ..Settings::default()
+        };
+        settings.pathsets.declare("");
+        match serde_json::to_value(settings) {
+            Ok(Value::Object(object)) => object.into_iter().map(|(key, _)| key).collect(),
+            _ => Vec::new(),
+        }
     }
 
     pub fn merge(&mut self, mut other: Settings) {
@@ -173,6 +252,7 @@ impl Settings {
         for pathset in other.pathsets.pathsets {
             self.pathsets.add(pathset);
         }
+        self.pathsets.declared.extend(other.pathsets.declared);
         self.goalmodes.extend(other.goalmodes);
         if other.spawn_loc != Spawn::default() {
             self.spawn_loc = other.spawn_loc;
@@ -182,7 +262,281 @@ impl Settings {
         self.hard = self.hard || other.hard;
         self.header_list.append(&mut other.header_list);
     }
-    
+
+    pub fn apply(&mut self, preset: Preset) {
+        let Preset { extends: _, worlds, players, pathsets, declared_pathsets, goalmodes, spawn_loc, spoilers, web_conn, hard, header_list, remove } = preset;
+
+        for pathset in &remove.pathsets {
+            self.pathsets.pathsets.remove(pathset);
+        }
+        for pathset in &remove.declared_pathsets {
+            self.pathsets.declared.remove(pathset);
+        }
+        for goalmode in &remove.goalmodes {
+            self.goalmodes.remove(goalmode);
+        }
+        self.players.retain(|player| !remove.players.contains(player));
+        self.header_list.retain(|header| !remove.header_list.iter().any(|removed| same_header(header, removed)));
+
+        if let Some(worlds) = worlds { self.worlds = worlds; }
+        if let Some(players) = players { self.players = players; }
+        for pathset in pathsets {
+            self.pathsets.add(pathset);
+        }
+        self.pathsets.declared.extend(declared_pathsets);
+        self.goalmodes.extend(goalmodes);
+        if let Some(spawn_loc) = spawn_loc { self.spawn_loc = spawn_loc; }
+        if let Some(spoilers) = spoilers { self.spoilers = spoilers; }
+        if let Some(web_conn) = web_conn { self.web_conn = web_conn; }
+        if let Some(hard) = hard { self.hard = hard; }
+        for header in header_list {
+            if !self.header_list.iter().any(|existing| same_header(existing, &header)) {
+                self.header_list.push(header);
+            }
+        }
+    }
+
+    /// Folds in a preset this one extends, which overrides whatever the presets extended before it set
+    pub fn inherit(&mut self, parent: Settings) {
+        self.apply(Preset {
+            worlds: Some(parent.worlds),
+            players: Some(parent.players),
+            pathsets: parent.pathsets.pathsets.into_iter().collect(),
+            declared_pathsets: parent.pathsets.declared.into_iter().collect(),
+            goalmodes: parent.goalmodes.into_iter().collect(),
+            spawn_loc: Some(parent.spawn_loc),
+            spoilers: Some(parent.spoilers),
+            web_conn: Some(parent.web_conn),
+            hard: Some(parent.hard),
+            header_list: parent.header_list,
+            ..Preset::default()
+        });
+    }
+
+    pub fn to_code(&self) -> Result<String, String> {
+        let mut bytes = vec![CODE_VERSION];
+
+        let mut flags = 0;
+        if self.spoilers { flags |= 1; }
+        if self.web_conn { flags |= 2; }
+        if self.hard { flags |= 4; }
+        bytes.push(flags);
+
+        write_number(&mut bytes, self.worlds);
+        write_number(&mut bytes, self.players.len());
+        for player in &self.players {
+            write_string(&mut bytes, player);
+        }
+
+        let pathsets = CODE_PATHSETS.iter().enumerate()
+            .filter(|(_, pathset)| self.pathsets.contains(**pathset))
+            .fold(0, |mask, (index, _)| mask | 1 << index);
+        write_number(&mut bytes, pathsets);
+        let goalmodes = CODE_GOALMODES.iter().enumerate()
+            .filter(|(_, goalmode)| self.goalmodes.contains(goalmode))
+            .fold(0, |mask, (index, _)| mask | 1 << index);
+        write_number(&mut bytes, goalmodes);
+
+        match &self.spawn_loc {
+            Spawn::Set(spawn) => {
+                bytes.push(0);
+                write_string(&mut bytes, spawn);
+            },
+            Spawn::Random => bytes.push(1),
+            Spawn::FullyRandom => bytes.push(2),
+        }
+
+        write_number(&mut bytes, self.header_list.len());
+        for header in &self.header_list {
+            let header = header.to_str().ok_or_else(|| format!("Header path {} is not valid unicode", header.display()))?;
+            write_string(&mut bytes, header);
+        }
+
+        // optional, so codes without declared pathsets stay the same
+        if !self.pathsets.declared.is_empty() {
+            let mut declared = self.pathsets.declared.iter().collect::<Vec<_>>();
+            declared.sort();
+            write_number(&mut bytes, declared.len());
+            for pathset in declared {
+                write_string(&mut bytes, pathset);
+            }
+        }
+
+        Ok(base64::encode_config(bytes, base64::URL_SAFE_NO_PAD))
+    }
+    pub fn from_code(code: &str) -> Result<Settings, String> {
+        let bytes = base64::decode_config(code.trim(), base64::URL_SAFE_NO_PAD).map_err(|err| format!("Invalid settings code: {}", err))?;
+        let mut reader = CodeReader { bytes: &bytes, index: 0 };
+
+        let version = reader.byte()?;
+        if version != CODE_VERSION {
+            return Err(format!("Unsupported settings code version {}, expected version {}", version, CODE_VERSION));
+        }
+
+        let flags = reader.byte()?;
+        let worlds = reader.number()?;
+        let player_count = reader.number()?;
+        let players = (0..player_count).map(|_| reader.string()).collect::<Result<Vec<_>, _>>()?;
+
+        let pathset_mask = reader.number()?;
+        let mut pathsets = Pathsets::default();
+        for (index, pathset) in CODE_PATHSETS.iter().enumerate() {
+            if pathset_mask & 1 << index != 0 { pathsets.add(*pathset); }
+        }
+        let goalmode_mask = reader.number()?;
+        let goalmodes = CODE_GOALMODES.iter().enumerate()
+            .filter(|(index, _)| goalmode_mask & 1 << index != 0)
+            .map(|(_, goalmode)| *goalmode)
+            .collect();
+
+        let spawn_loc = match reader.byte()? {
+            0 => Spawn::Set(reader.string()?),
+            1 => Spawn::Random,
+            2 => Spawn::FullyRandom,
+            other => return Err(format!("Invalid spawn type {} in settings code", other)),
+        };
+
+        let header_count = reader.number()?;
+        let header_list = (0..header_count).map(|_| reader.string().map(PathBuf::from)).collect::<Result<Vec<_>, _>>()?;
+
+        if reader.index < bytes.len() {
+            let declared_count = reader.number()?;
+            pathsets.declared = (0..declared_count).map(|_| reader.string()).collect::<Result<_, _>>()?;
+        }
+
+        if reader.index < bytes.len() {
+            return Err(String::from("Invalid settings code: unexpected data at the end"));
+        }
+
+        Ok(Settings {
+            version: None,
+            worlds,
+            players,
+            pathsets,
+            goalmodes,
+            spawn_loc,
+            spoilers: flags & 1 != 0,
+            web_conn: flags & 2 != 0,
+            hard: flags & 4 != 0,
+            header_list,
+        })
+    }
+}
+
+// The order of these may never change, only append to them
+const CODE_VERSION: u8 = 1;
+const CODE_PATHSETS: [Pathset; 8] = [
+    Pathset::Moki,
+    Pathset::Gorlek,
+    Pathset::Unsafe,
+    Pathset::SwordSentryJump,
+    Pathset::HammerSentryJump,
+    Pathset::ShurikenBreak,
+    Pathset::SentryBurn,
+    Pathset::RemoveKillPlane,
+];
+const CODE_GOALMODES: [GoalMode; 4] = [
+    GoalMode::Wisps,
+    GoalMode::Trees,
+    GoalMode::Quests,
+    GoalMode::Relics,
+];
+
+// numbers are written as LEB128 to keep the common small values at one byte
+fn write_number(bytes: &mut Vec<u8>, mut number: usize) {
+    loop {
+        #[allow(clippy::cast_possible_truncation)]
+        let byte = (number & 0x7f) as u8;
+        number >>= 7;
+        if number == 0 {
+            bytes.push(byte);
+            break;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+fn write_string(bytes: &mut Vec<u8>, string: &str) {
+    write_number(bytes, string.len());
+    bytes.extend_from_slice(string.as_bytes());
+}
+
+struct CodeReader<'a> {
+    bytes: &'a [u8],
+    index: usize,
+}
+impl CodeReader<'_> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let byte = *self.bytes.get(self.index).ok_or_else(|| String::from("Invalid settings code: unexpected end"))?;
+        self.index += 1;
+        Ok(byte)
+    }
+    fn number(&mut self) -> Result<usize, String> {
+        let mut number = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            if shift >= usize::BITS {
+                return Err(String::from("Invalid settings code: number out of range"));
+            }
+            number |= usize::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 { return Ok(number); }
+            shift += 7;
+        }
+    }
+    fn string(&mut self) -> Result<String, String> {
+        let length = self.number()?;
+        let end = self.index.checked_add(length).filter(|&end| end <= self.bytes.len()).ok_or_else(|| String::from("Invalid settings code: unexpected end"))?;
+        let string = String::from_utf8(self.bytes[self.index..end].to_vec()).map_err(|_| String::from("Invalid settings code: invalid text"))?;
+        self.index = end;
+        Ok(string)
+    }
+}
+
+fn same_header(a: &Path, b: &Path) -> bool {
+    a.with_extension("") == b.with_extension("")
+}
+
+/// A preset file, which may extend other presets and only has to mention the settings it changes
+///
+/// Lists get added to the parent settings, single values override them
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Preset {
+    pub extends: Vec<String>,
+    pub worlds: Option<usize>,
+    pub players: Option<Vec<String>>,
+    pub pathsets: Vec<Pathset>,
+    pub declared_pathsets: Vec<String>,
+    pub goalmodes: Vec<GoalMode>,
+    pub spawn_loc: Option<Spawn>,
+    pub spoilers: Option<bool>,
+    pub web_conn: Option<bool>,
+    pub hard: Option<bool>,
+    pub header_list: Vec<PathBuf>,
+    pub remove: PresetRemovals,
+}
+/// Entries a [`Preset`] removes from the settings it extends
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PresetRemovals {
+    pub players: Vec<String>,
+    pub pathsets: Vec<Pathset>,
+    pub declared_pathsets: Vec<String>,
+    pub goalmodes: Vec<GoalMode>,
+    pub header_list: Vec<PathBuf>,
+}
+impl Preset {
+    pub fn from_file(preset: &Path) -> Result<Preset, String> {
+        let content = super::read_file(preset, "presets")?;
+        let mut value: Value = serde_json::from_str(&content).map_err(|err| format!("Failed to read preset from {}: {}", preset.display(), err))?;
+        if let Some(object) = value.as_object_mut() {
+            object.remove("schemaVersion");
+            let mut known = Settings::fields();
+            known.extend(["extends", "remove"].iter().map(|field| field.to_string()));
+            warn_unknown_fields(object, &known);
+        }
+        serde_json::from_value(value).map_err(|err| format!("Failed to read preset from {}: {}", preset.display(), err))
+    }
 }
 
 pub fn read_spawn(seed: &str) -> Result<String, String> {
@@ -193,3 +547,115 @@ pub fn read_spawn(seed: &str) -> Result<String, String> {
     }
     Ok(DEFAULT_SPAWN.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_code() {
+        let settings = Settings::default();
+        assert_eq!(Settings::from_code(&settings.to_code().unwrap()).unwrap(), settings);
+
+        let mut pathsets = Pathsets::default();
+        pathsets.add(Pathset::Unsafe);
+        pathsets.add(Pathset::SentryBurn);
+        pathsets.declare("kii");
+        let mut goalmodes = FxHashSet::default();
+        goalmodes.insert(GoalMode::Trees);
+        goalmodes.insert(GoalMode::Relics);
+        let settings = Settings {
+            version: None,
+            worlds: 3,
+            players: vec![String::from("Ori"), String::from("Kü")],
+            pathsets,
+            goalmodes,
+            spawn_loc: Spawn::Set(String::from("GladesTown.Teleporter")),
+            spoilers: false,
+            web_conn: true,
+            hard: true,
+            header_list: vec![PathBuf::from("hints"), PathBuf::from("qol")],
+        };
+        let code = settings.to_code().unwrap();
+        assert!(code.chars().all(|char| char.is_ascii_alphanumeric() || char == '-' || char == '_'));
+        let decoded = Settings::from_code(&code).unwrap();
+        assert_eq!(decoded, settings);
+        let written: Settings = serde_json::from_str(&Settings::write(&settings).unwrap()).unwrap();
+        assert_eq!(decoded, written);
+
+        assert!(Settings::from_code("").is_err());
+        assert!(Settings::from_code(&code[..code.len() - 4]).is_err());
+        assert!(Settings::from_code("not a code").is_err());
+    }
+
+    #[test]
+    fn settings_migration() {
+        let java = r#"{"tps":true,"spoilers":false,"unsafePaths":false,"gorlekPaths":true,"glitchPaths":false,"questLocs":true,"outputFolder":"","flags":{"forceWisps":false,"forceTrees":true,"forceQuests":false,"worldTour":false,"noHints":false,"noSword":false,"rain":false,"noKSDoors":false,"randomSpawn":false},"webConn":false,"bonusItems":false,"debugInfo":false,"seirLaunch":false,"spawnLoc":"MarshSpawn.Main","headerList":[]}"#;
+        let settings = Settings::parse(java).unwrap();
+        assert!(settings.pathsets.contains(Pathset::Gorlek));
+        assert!(settings.goalmodes.contains(&GoalMode::Trees));
+        assert!(!settings.spoilers);
+        assert!(settings.header_list.contains(&PathBuf::from("teleporters")));
+
+        let unversioned = r#"{"worlds":1,"players":[],"pathsets":["Moki","Gorlek"],"goalmodes":[],"spawnLoc":"Random","spoilers":true,"webConn":false,"headerList":["qol"],"outdated":true}"#;
+        let settings = Settings::parse(unversioned).unwrap();
+        assert_eq!(settings.spawn_loc, Spawn::Random);
+        assert!(!settings.hard);
+        assert_eq!(Settings::parse(&Settings::write(&settings).unwrap()).unwrap(), settings);
+
+        assert!(Settings::parse(r#"{"schemaVersion":999}"#).is_err());
+        assert!(Settings::from_seed("3|0|6|message\n// Config: [1, 2]").is_err());
+    }
+
+    #[test]
+    fn preset_inheritance() {
+        let mut settings = Settings {
+            header_list: vec![PathBuf::from("hints"), PathBuf::from("qol")],
+            ..Settings::default()
+        };
+        settings.goalmodes.insert(GoalMode::Wisps);
+
+        let preset: Preset = serde_json::from_str(r#"{
+            "extends": ["moki"],
+            "pathsets": ["Gorlek"],
+            "goalmodes": ["Trees"],
+            "spoilers": false,
+            "headerList": ["qol", "no_rain"],
+            "remove": { "goalmodes": ["Wisps"], "headerList": ["hints.wotwrh"] }
+        }"#).unwrap();
+        assert_eq!(preset.extends, vec![String::from("moki")]);
+        settings.apply(preset);
+
+        assert!(settings.pathsets.contains(Pathset::Gorlek));
+        assert_eq!(settings.goalmodes.iter().collect::<Vec<_>>(), vec![&GoalMode::Trees]);
+        assert!(!settings.spoilers);
+        assert_eq!(settings.spawn_loc, Spawn::default());
+        assert_eq!(settings.header_list, vec![PathBuf::from("qol"), PathBuf::from("no_rain")]);
+
+        let full = Settings::write(&settings).unwrap();
+        assert!(full.starts_with(r#"{"schemaVersion":1,"#));
+        let mut reapplied = Settings::default();
+        reapplied.apply(serde_json::from_str(&full).unwrap());
+        assert_eq!(reapplied, settings);
+
+        // the later of two parents wins, and headers they share are only listed once
+        let first = Settings {
+            spawn_loc: Spawn::Random,
+            spoilers: false,
+            header_list: vec![PathBuf::from("hints"), PathBuf::from("qol")],
+            ..Settings::default()
+        };
+        let second = Settings {
+            spawn_loc: Spawn::default(),
+            spoilers: true,
+            header_list: vec![PathBuf::from("qol.wotwrh"), PathBuf::from("no_rain")],
+            ..Settings::default()
+        };
+        let mut settings = Settings::default();
+        settings.inherit(first);
+        settings.inherit(second);
+        assert_eq!(settings.spawn_loc, Spawn::default());
+        assert!(settings.spoilers);
+        assert_eq!(settings.header_list, vec![PathBuf::from("hints"), PathBuf::from("qol"), PathBuf::from("no_rain")]);
+    }
+}
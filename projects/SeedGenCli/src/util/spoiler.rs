@@ -0,0 +1,71 @@
+use chacha20poly1305::{
+    ChaCha20Poly1305, Key, Nonce,
+    aead::{Aead, KeyInit},
+};
+use rand::RngCore;
+use sha2::Sha256;
+
+const LOCKED_HEADER: &str = "// Locked spoiler, use \"seedgen spoiler unlock\" with the passphrase to read it";
+const LOCK_VERSION: u8 = 1;
+const KEY_ROUNDS: u32 = 100_000;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Key {
+    let mut key = Key::default();
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, KEY_ROUNDS, &mut key);
+    key
+}
+
+/// Encrypts a spoiler so it can be handed out with the seed and only read with the passphrase
+pub fn lock(spoiler: &str, passphrase: &str) -> Result<String, String> {
+    let mut rng = rand::thread_rng();
+    let mut salt = [0; SALT_LENGTH];
+    rng.fill_bytes(&mut salt);
+    let mut nonce = [0; NONCE_LENGTH];
+    rng.fill_bytes(&mut nonce);
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt));
+    let encrypted = cipher.encrypt(Nonce::from_slice(&nonce), spoiler.as_bytes()).map_err(|_| String::from("Failed to encrypt spoiler"))?;
+
+    let mut bytes = Vec::with_capacity(1 + SALT_LENGTH + NONCE_LENGTH + encrypted.len());
+    bytes.push(LOCK_VERSION);
+    bytes.extend_from_slice(&salt);
+    bytes.extend_from_slice(&nonce);
+    bytes.extend(encrypted);
+
+    Ok(format!("{}\n{}\n", LOCKED_HEADER, base64::encode(bytes)))
+}
+
+pub fn unlock(locked: &str, passphrase: &str) -> Result<String, String> {
+    let encoded = locked.lines().find(|line| !line.starts_with("//") && !line.trim().is_empty()).ok_or_else(|| String::from("This is not a locked spoiler"))?;
+    let bytes = base64::decode(encoded.trim()).map_err(|err| format!("This is not a locked spoiler: {}", err))?;
+
+    if bytes.len() < 1 + SALT_LENGTH + NONCE_LENGTH {
+        return Err(String::from("Locked spoiler is incomplete"));
+    }
+    if bytes[0] != LOCK_VERSION {
+        return Err(format!("Unsupported spoiler lock version {}", bytes[0]));
+    }
+    let (salt, rest) = bytes[1..].split_at(SALT_LENGTH);
+    let (nonce, encrypted) = rest.split_at(NONCE_LENGTH);
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, salt));
+    let spoiler = cipher.decrypt(Nonce::from_slice(nonce), encrypted).map_err(|_| String::from("Failed to unlock spoiler, is the passphrase correct?"))?;
+    String::from_utf8(spoiler).map_err(|_| String::from("Unlocked spoiler is not valid text"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spoiler_lock() {
+        let spoiler = "Flags: ForceTrees\n\n3|0|6|Seed hash: Ku Ori Sein Moki|f=600\n";
+        let locked = lock(spoiler, "kwolok").unwrap();
+        assert!(!locked.contains("ForceTrees"));
+        assert_eq!(unlock(&locked, "kwolok").unwrap(), spoiler);
+        assert!(unlock(&locked, "Kwolok").is_err());
+        assert!(unlock(spoiler, "kwolok").is_err());
+    }
+}
@@ -52,6 +52,17 @@ impl UberState {
         }
     }
 
+    /// Whether an uber state holding the value counts as this one
+    ///
+    /// Numeric states like quest progress count once they reach the value, since they only ever go up
+    pub fn is_met(&self, value: &UberValue) -> bool {
+        match value {
+            UberValue::Bool(value) => *value && self.value.is_empty(),
+            UberValue::Int(value) => if self.value.is_empty() { *value != 0 } else { self.value.parse::<i32>().is_ok_and(|needed| *value >= needed) },
+            UberValue::Float(value) => if self.value.is_empty() { *value != 0.0 } else { self.value.parse::<f32>().is_ok_and(|needed| *value >= needed) },
+        }
+    }
+
     pub fn is_shop(&self) -> bool {
         self.identifier.uber_group == 1 ||
         self.identifier.uber_group == 2 ||
@@ -72,7 +83,7 @@ impl fmt::Display for UberState {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum UberValue {
     Bool(bool),
     Int(i32),
@@ -100,5 +111,15 @@ mod tests {
         assert_eq!(format!("{}", uber_state), "25432|65195=11");
         assert!(UberState::from_parts("", "3").is_err());
         assert!(UberState::from_parts("a", "3").is_err());
+
+        let quest = UberState::from_parts("48248", "51645=3").unwrap();
+        assert!(!quest.is_met(&UberValue::Int(2)));
+        assert!(quest.is_met(&UberValue::Int(3)));
+        assert!(quest.is_met(&UberValue::Int(4)));
+        let flag = UberState::from_parts("21786", "25095").unwrap();
+        assert!(flag.is_met(&UberValue::Bool(true)));
+        assert!(!flag.is_met(&UberValue::Bool(false)));
+        assert!(flag.is_met(&UberValue::Int(1)));
+        assert!(!flag.is_met(&UberValue::Int(0)));
     }
 }
@@ -3,9 +3,10 @@ use std::fmt;
 use rustc_hash::{FxHashMap, FxHashSet};
 use smallvec::{SmallVec, smallvec};
 
-use super::{player::Player, requirements::Requirement};
+use super::{player::Player, requirements::{Requirement, compiled::CompiledRequirements}};
+use crate::inventory::PlayerInventory;
 use crate::util::{
-    RefillType, NodeType,
+    RefillType, NodeType, DeclaredPathset,
     Position,
     orbs::{self, Orbs},
     uberstate::{UberState, UberValue, UberIdentifier},
@@ -16,12 +17,16 @@ use crate::util::{
 pub struct Refill {
     pub name: RefillType,
     pub requirement: Requirement,
+    /// Index of the requirement in [`Graph::requirements`]
+    pub compiled: usize,
 }
 
 #[derive(Debug)]
 pub struct Connection {
     pub to: usize,
     pub requirement: Requirement,
+    /// Index of the requirement in [`Graph::requirements`]
+    pub compiled: usize,
 }
 
 #[derive(Debug)]
@@ -123,127 +128,160 @@ impl fmt::Display for Node {
 type Reached<'a> = Vec<&'a Node>;
 type Progressions<'a> = Vec<(&'a Requirement, SmallVec<[Orbs; 3]>)>;
 
+type PendingConnections = FxHashMap<(usize, usize), SmallVec<[Orbs; 3]>>;
+
 struct ReachContext<'a, 'b> {
     player: &'b Player,
-    progression_check: bool,
+    spawn: usize,
     states: FxHashSet<usize>,
     state_progressions: FxHashMap<usize, Vec<(usize, &'a Connection)>>,
-    world_state: FxHashMap<usize, SmallVec<[Orbs; 3]>>
+    world_state: FxHashMap<usize, SmallVec<[Orbs; 3]>>,
+    // anchors that were reached again with better orbs
+    revisits: Vec<usize>,
+    // connections by anchor and connection index that more items could open up or make cheaper, with the orbs they were tried with
+    pending: PendingConnections,
+    // anchors with refills that weren't met
+    pending_refills: FxHashSet<usize>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct Graph {
     pub nodes: Vec<Node>,
+    pub requirements: CompiledRequirements,
+    pub pathsets: Vec<DeclaredPathset>,
 }
 impl Graph {
     fn follow_state_progressions<'a>(
         &'a self,
         index: usize,
         context: &mut ReachContext<'a, '_>,
-    ) -> (Reached<'a>, Progressions<'a>) {
+    ) -> Reached<'a> {
         let mut reached = Vec::new();
-        let mut progressions = Vec::new();
         if let Some(connections) = context.state_progressions.get(&index) {
             for (from, connection) in connections.clone() {
-                if context.world_state.contains_key(&connection.to) {
-                    // TODO loop with improved orbs?
-                    continue;
-                }
-                let target_orbs = Graph::try_connection(context.player, connection, &context.world_state[&from], &context.states);
-                if !target_orbs.is_empty() {
-                    let (mut child_reached, mut child_progressions) = self.reach_recursion(&self.nodes[connection.to], false, target_orbs, context);
-                    reached.append(&mut child_reached);
-                    progressions.append(&mut child_progressions);
-                }
+                if !self.can_improve(connection.to, context) { continue; }
+                let target_orbs = self.try_requirement(context.player, connection.compiled, &context.world_state[&from], &context.states);
+                reached.append(&mut self.visit(connection.to, target_orbs, context));
             }
         }
-        (reached, progressions)
+        reached
     }
-    fn try_connection(player: &Player, connection: &Connection, best_orbs: &[Orbs], states: &FxHashSet<usize>) -> SmallVec<[Orbs; 3]> {
+    fn try_requirement(&self, player: &Player, requirement: usize, best_orbs: &[Orbs], states: &FxHashSet<usize>) -> SmallVec<[Orbs; 3]> {
         let mut target_orbs = SmallVec::<[Orbs; 3]>::default();
         for orbs in best_orbs {
-            if let Some(orbcost) = connection.requirement.is_met(player, states, *orbs) {
+            if let Some(orbcost) = self.requirements.is_met(requirement, player, states, *orbs) {
                 target_orbs.append(&mut orbs::both_single(&orbcost, *orbs));
             }
         }
         target_orbs
     }
 
+    // Only anchors can make use of more orbs, anything else is done after the first visit
+    fn can_improve(&self, index: usize, context: &ReachContext) -> bool {
+        !context.world_state.contains_key(&index) || matches!(self.nodes[index], Node::Anchor(_))
+    }
+    // Enters a node, or queues a revisit if the orbs are better than on any earlier visit
+    fn visit<'a>(
+        &'a self,
+        index: usize,
+        target_orbs: SmallVec<[Orbs; 3]>,
+        context: &mut ReachContext<'a, '_>,
+    ) -> Reached<'a> {
+        if target_orbs.is_empty() { return Vec::new(); }
+        match context.world_state.get(&index) {
+            Some(prior) => {
+                if self.can_improve(index, context) && orbs::improves(prior, &target_orbs) {
+                    let best_orbs = orbs::merge(prior, &target_orbs);
+                    context.world_state.insert(index, best_orbs);
+                    if !context.revisits.contains(&index) {
+                        context.revisits.push(index);
+                    }
+                }
+                Vec::new()
+            },
+            None => self.reach_recursion(&self.nodes[index], false, target_orbs, context),
+        }
+    }
+    // Revisiting right away could recurse around loops for a long time, so it happens once the paths from the first visits are done
+    fn revisit<'a>(&'a self, context: &mut ReachContext<'a, '_>) -> Reached<'a> {
+        let mut reached = Vec::new();
+        while let Some(index) = context.revisits.pop() {
+            let best_orbs = context.world_state[&index].clone();
+            reached.append(&mut self.reach_recursion(&self.nodes[index], index == context.spawn, best_orbs, context));
+        }
+        reached
+    }
+
     fn reach_recursion<'a>(
         &'a self,
         entry: &'a Node,
         is_spawn: bool,
         mut best_orbs: SmallVec<[Orbs; 3]>,
         context: &mut ReachContext<'a, '_>,
-    ) -> (Reached<'a>, Progressions<'a>) {
+    ) -> Reached<'a> {
         context.world_state.insert(entry.index(), best_orbs.clone());
         match entry {
             Node::Anchor(anchor) => {
                 for refill in &anchor.refills {
-                    for orbs in &best_orbs {
-                        if let Some(orbcost) = refill.requirement.is_met(context.player, &context.states, *orbs) {
-                            best_orbs = orbs::both(&best_orbs, &orbcost);
-                            match refill.name {
-                                RefillType::Full => best_orbs = smallvec![context.player.max_orbs()],
-                                RefillType::Checkpoint => best_orbs = context.player.checkpoint_orbs(&best_orbs),
-                                RefillType::Health(amount) => best_orbs = context.player.health_orbs(&best_orbs, amount),
-                                RefillType::Energy(amount) => best_orbs = context.player.energy_orbs(&best_orbs, amount),
-                            }
-                            break;
-                        }
+                    // each of the orbs pays its own cost for the refill
+                    let refilled = self.try_requirement(context.player, refill.compiled, &best_orbs, &context.states);
+                    if refilled.is_empty() {
+                        context.pending_refills.insert(anchor.index);
+                        continue;
                     }
+                    let refilled = match refill.name {
+                        RefillType::Full => smallvec![context.player.max_orbs()],
+                        RefillType::Checkpoint => context.player.checkpoint_orbs(&refilled),
+                        RefillType::Health(amount) => context.player.health_orbs(&refilled, amount),
+                        RefillType::Energy(amount) => context.player.energy_orbs(&refilled, amount),
+                    };
+                    // skipping the refill is always an option
+                    best_orbs = orbs::merge(&best_orbs, &refilled);
                 }
 
                 let mut reached = Vec::new();
-                let mut progressions = Vec::new();
-                for connection in &anchor.connections {
-                    if context.world_state.contains_key(&connection.to) {
-                        // TODO loop with improved orbs?
-                        continue;
+                for (connection_index, connection) in anchor.connections.iter().enumerate() {
+                    if !self.can_improve(connection.to, context) { continue; }
+                    let target_orbs = self.try_requirement(context.player, connection.compiled, &best_orbs, &context.states);
+                    if target_orbs == best_orbs {
+                        context.pending.remove(&(anchor.index, connection_index));
+                    } else {
+                        context.pending.insert((anchor.index, connection_index), best_orbs.clone());
                     }
-                    let target_orbs = Graph::try_connection(context.player, connection, &best_orbs, &context.states);
-                    if target_orbs.is_empty() {
-                        let mut states = connection.requirement.contained_states();
-                        states.retain(|state| !context.states.contains(state));
 
-                        if states.is_empty() {
-                            if context.progression_check {
-                                progressions.push((&connection.requirement, best_orbs.clone()))
-                            }
-                        } else {
-                            for state in states {
-                                context.state_progressions.entry(state).or_default().push((anchor.index, connection));
+                    if target_orbs.is_empty() {
+                        for &state in self.requirements.contained_states(connection.compiled) {
+                            if context.states.contains(&state) { continue; }
+                            let state_progressions = context.state_progressions.entry(state).or_default();
+                            if !state_progressions.iter().any(|&(_, other)| std::ptr::eq(other, connection)) {
+                                state_progressions.push((anchor.index, connection));
                             }
                         }
                     } else {
-                        let (mut child_reached, mut child_progressions) = self.reach_recursion(&self.nodes[connection.to], false, target_orbs, context);
-                        reached.append(&mut child_reached);
-                        progressions.append(&mut child_progressions);
+                        reached.append(&mut self.visit(connection.to, target_orbs, context));
                     }
                 }
                 if is_spawn {
                     if let Some(tp_anchor) = self.nodes.iter().find(|&node| node.identifier() == TP_ANCHOR) {
                         if !anchor.connections.iter().any(|connection| connection.to == tp_anchor.index()) {
-                            let (mut tp_reached, mut tp_progressions) = self.reach_recursion(&tp_anchor, false, best_orbs, context);
-                            reached.append(&mut tp_reached);
-                            progressions.append(&mut tp_progressions);
+                            reached.append(&mut self.visit(tp_anchor.index(), best_orbs, context));
                         }
                     }
                 }
-                (reached, progressions)
+                reached
             },
-            Node::Pickup(_) => (vec![entry], vec![]),
+            Node::Pickup(_) => vec![entry],
             Node::State(state) => {
                 context.states.insert(state.index);
-                let (mut reached, progressions) = self.follow_state_progressions(state.index, context);
+                let mut reached = self.follow_state_progressions(state.index, context);
                 reached.push(entry);
-                (reached, progressions)
+                reached
             },
             Node::Quest(quest) => {
                 context.states.insert(quest.index);
-                let (mut reached, progressions) = self.follow_state_progressions(quest.index, context);
+                let mut reached = self.follow_state_progressions(quest.index, context);
                 reached.push(entry);
-                (reached, progressions)
+                reached
             },
         }
     }
@@ -260,10 +298,8 @@ impl Graph {
                 Node::Quest(quest) => (&quest.uber_state, &quest.index),
                 _ => continue,
             };
-            if let Some(value) = extra_states.get(&uber_state.identifier) {
-                if format!("{}", value) == uber_state.value {
-                    states.insert(*index);
-                }
+            if extra_states.get(&uber_state.identifier).is_some_and(|value| uber_state.is_met(value)) {
+                states.insert(*index);
             }
         }
 
@@ -278,38 +314,310 @@ impl Graph {
     }
 
     pub fn reached_locations<'a>(&'a self, player: &Player, spawn: &'a Node, extra_states: &FxHashMap<UberIdentifier, UberValue>) -> Result<Reached<'a>, String> {
-        let mut context = ReachContext {
-            player,
-            progression_check: false,
-            states: self.collect_extra_states(extra_states),
+        let mut reach = IncrementalReach::new(self, spawn);
+        reach.update(player, extra_states);
+
+        Ok(reach.reached)
+    }
+    pub fn reached_and_progressions<'a>(&'a self, player: &Player, spawn: &'a Node, extra_states: &FxHashMap<UberIdentifier, UberValue>) -> Result<(Reached<'a>, Progressions<'a>), String> {
+        let mut reach = IncrementalReach::new(self, spawn);
+        reach.update(player, extra_states);
+        let progressions = reach.progressions();
+
+        Ok((reach.reached, progressions))
+    }
+}
+
+/// Keeps what has been reached so far, so that reaching further with more items only has to look at what was out of reach before
+///
+/// Losing items or uber states starts over from spawn
+#[derive(Debug, Clone)]
+pub struct IncrementalReach<'a> {
+    graph: &'a Graph,
+    spawn: &'a Node,
+    inventory: PlayerInventory,
+    max_orbs: Orbs,
+    extra_states: FxHashSet<usize>,
+    states: FxHashSet<usize>,
+    state_progressions: FxHashMap<usize, Vec<(usize, &'a Connection)>>,
+    world_state: FxHashMap<usize, SmallVec<[Orbs; 3]>>,
+    pending: PendingConnections,
+    pending_refills: FxHashSet<usize>,
+    reached: Reached<'a>,
+}
+impl<'a> IncrementalReach<'a> {
+    pub fn new(graph: &'a Graph, spawn: &'a Node) -> IncrementalReach<'a> {
+        IncrementalReach {
+            graph,
+            spawn,
+            inventory: PlayerInventory::default(),
+            max_orbs: Orbs::default(),
+            extra_states: FxHashSet::default(),
+            states: FxHashSet::default(),
             state_progressions: FxHashMap::default(),
             world_state: FxHashMap::default(),
-        };
+            pending: FxHashMap::default(),
+            pending_refills: FxHashSet::default(),
+            reached: Vec::new(),
+        }
+    }
 
-        let (reached, _) = self.reach_recursion(spawn, true, smallvec![player.max_orbs()], &mut context);
+    /// Reaches as far as the player can get now
+    pub fn update(&mut self, player: &Player, extra_states: &FxHashMap<UberIdentifier, UberValue>) {
+        let graph = self.graph;
+        let extra_states = graph.collect_extra_states(extra_states);
+        let max_orbs = player.max_orbs();
+
+        let start_over = self.world_state.is_empty()
+            || max_orbs.health < self.max_orbs.health
+            || max_orbs.energy < self.max_orbs.energy
+            || !player.inventory.contains(&self.inventory)
+            || !extra_states.is_superset(&self.extra_states);
+        if start_over {
+            *self = IncrementalReach::new(graph, self.spawn);
+        }
 
-        Ok(reached)
-    }
-    pub fn reached_and_progressions<'a>(&'a self, player: &Player, spawn: &'a Node, extra_states: &FxHashMap<UberIdentifier, UberValue>) -> Result<(Reached<'a>, Progressions<'a>), String> {
         let mut context = ReachContext {
             player,
-            progression_check: true,
-            states: self.collect_extra_states(extra_states),
-            state_progressions: FxHashMap::default(),
-            world_state: FxHashMap::default(),
+            spawn: self.spawn.index(),
+            states: std::mem::take(&mut self.states),
+            state_progressions: std::mem::take(&mut self.state_progressions),
+            world_state: std::mem::take(&mut self.world_state),
+            revisits: Vec::new(),
+            pending: FxHashMap::default(),
+            pending_refills: FxHashSet::default(),
         };
+        context.states.extend(extra_states.iter().copied());
+
+        if start_over {
+            self.reached = graph.reach_recursion(self.spawn, true, smallvec![max_orbs], &mut context);
+        } else {
+            if max_orbs != self.max_orbs {
+                // refills and the orbs at spawn depend on the max orbs
+                let mut refill_anchors = graph.nodes.iter()
+                    .filter(|node| matches!(node, Node::Anchor(anchor) if !anchor.refills.is_empty()) && context.world_state.contains_key(&node.index()))
+                    .map(Node::index)
+                    .collect::<Vec<_>>();
+                context.revisits.append(&mut refill_anchors);
+                self.reached.append(&mut graph.visit(self.spawn.index(), smallvec![max_orbs], &mut context));
+            }
+
+            let mut pending_refills = self.pending_refills.drain().collect::<Vec<_>>();
+            pending_refills.sort_unstable();
+            for index in pending_refills {
+                let best_orbs = context.world_state[&index].clone();
+                self.reached.append(&mut graph.reach_recursion(&graph.nodes[index], index == context.spawn, best_orbs, &mut context));
+            }
+
+            let mut pending = self.pending.drain().collect::<Vec<_>>();
+            pending.sort_unstable_by_key(|&(key, _)| key);
+            for ((from, connection_index), best_orbs) in pending {
+                let connection = match &graph.nodes[from] {
+                    Node::Anchor(anchor) => &anchor.connections[connection_index],
+                    _ => continue,
+                };
+                if !graph.can_improve(connection.to, &context) { continue; }
+                let target_orbs = graph.try_requirement(player, connection.compiled, &best_orbs, &context.states);
+                if target_orbs != best_orbs {
+                    // revisiting the anchor in the meantime would have tried it with better orbs already
+                    context.pending.entry((from, connection_index)).or_insert_with(|| best_orbs.clone());
+                }
+                self.reached.append(&mut graph.visit(connection.to, target_orbs, &mut context));
+            }
+        }
+        self.reached.append(&mut graph.revisit(&mut context));
 
-        let (reached, mut progressions) = self.reach_recursion(spawn, true, smallvec![player.max_orbs()], &mut context);
+        self.inventory = player.inventory.clone();
+        self.max_orbs = max_orbs;
+        self.extra_states = extra_states;
+        self.states = context.states;
+        self.state_progressions = context.state_progressions;
+        self.world_state = context.world_state;
+        self.pending = context.pending;
+        self.pending_refills = context.pending_refills;
+    }
+
+    /// Every node reached so far
+    pub fn reached(&self) -> &[&'a Node] {
+        &self.reached
+    }
+
+    /// The requirements of connections that could lead further, with the orbs available when trying them
+    pub fn progressions(&self) -> Progressions<'a> {
+        let mut progressions: Progressions = Vec::new();
+        let mut seen: Vec<&Connection> = Vec::new();
+
+        let mut pending = self.pending.iter().collect::<Vec<_>>();
+        pending.sort_unstable_by_key(|&(key, _)| key);
+        for (&(from, connection_index), best_orbs) in pending {
+            let connection = match &self.graph.nodes[from] {
+                Node::Anchor(anchor) => &anchor.connections[connection_index],
+                _ => continue,
+            };
+            if self.world_state.contains_key(&connection.to) { continue; }
+            // these get added below
+            if self.graph.requirements.contained_states(connection.compiled).iter().any(|state| !self.states.contains(state)) { continue; }
+
+            seen.push(connection);
+            progressions.push((&connection.requirement, best_orbs.clone()));
+        }
 
         // add progressions containing states that were never met
-        for (_, state_progressions) in context.state_progressions {
-            for (from, connection) in state_progressions {
-                if !context.world_state.contains_key(&connection.to) {
-                    progressions.push((&connection.requirement, context.world_state[&from].clone()))
+        let mut state_progressions = self.state_progressions.iter().collect::<Vec<_>>();
+        state_progressions.sort_unstable_by_key(|&(state, _)| state);
+        for (_, state_progressions) in state_progressions {
+            for &(from, connection) in state_progressions {
+                if !self.world_state.contains_key(&connection.to) && !seen.iter().any(|&other| std::ptr::eq(other, connection)) {
+                    seen.push(connection);
+                    progressions.push((&connection.requirement, self.world_state[&from].clone()))
                 }
             }
         }
 
-        Ok((reached, progressions))
+        progressions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use crate::lexer;
+    use crate::inventory::Item;
+    use crate::util::{Resource, Skill, Shard, Teleporter, Pathsets, settings::Settings};
+
+    fn anchor(index: usize, refills: Vec<(RefillType, Requirement)>, connections: Vec<(usize, Requirement)>, requirements: &mut CompiledRequirements) -> Node {
+        Node::Anchor(Anchor {
+            identifier: format!("Anchor{}", index),
+            position: None,
+            index,
+            refills: refills.into_iter().map(|(name, requirement)| Refill { name, compiled: requirements.compile(&requirement), requirement }).collect(),
+            connections: connections.into_iter().map(|(to, requirement)| Connection { to, compiled: requirements.compile(&requirement), requirement }).collect(),
+        })
+    }
+
+    #[test]
+    fn revisit_with_better_orbs() {
+        let build_graph = |refill_path: Requirement| {
+            let mut requirements = CompiledRequirements::default();
+            let nodes = vec![
+                anchor(0, vec![], vec![(1, Requirement::Damage(20.0)), (2, Requirement::Free)], &mut requirements),
+                anchor(1, vec![], vec![(3, Requirement::Damage(25.0))], &mut requirements),
+                anchor(2, vec![(RefillType::Full, Requirement::Free)], vec![(1, refill_path)], &mut requirements),
+                Node::Pickup(Pickup {
+                    identifier: String::from("Pickup"),
+                    position: Position::default(),
+                    zone: String::new(),
+                    index: 3,
+                    uber_state: UberState::from_parts("1", "1").unwrap(),
+                }),
+            ];
+            Graph { nodes, requirements, ..Graph::default() }
+        };
+        let mut player = Player::default();
+        player.inventory.grant(Item::Resource(Resource::Health), 6);
+
+        // the first path to Anchor1 leaves too little health, but the second one refills on the way
+        let graph = build_graph(Requirement::Free);
+        let (reached, progressions) = graph.reached_and_progressions(&player, &graph.nodes[0], &FxHashMap::default()).unwrap();
+        assert_eq!(reached.iter().map(|node| node.index()).collect::<Vec<_>>(), vec![3]);
+        assert!(progressions.is_empty());
+
+        let graph = build_graph(Requirement::Impossible);
+        let (reached, progressions) = graph.reached_and_progressions(&player, &graph.nodes[0], &FxHashMap::default()).unwrap();
+        assert!(reached.is_empty());
+        assert_eq!(progressions.len(), 1);
+        assert!(progressions.iter().any(|(requirement, best_orbs)| matches!(requirement, Requirement::Damage(_)) && best_orbs[..] == [Orbs { health: 10.0, energy: 0.0 }]));
+    }
+
+    #[test]
+    fn quest_progress() {
+        let mut requirements = CompiledRequirements::default();
+        let quest = UberState::from_parts("48248", "51645=3").unwrap();
+        let nodes = vec![
+            anchor(0, vec![], vec![(1, Requirement::State(2))], &mut requirements),
+            Node::Pickup(Pickup {
+                identifier: String::from("Pickup"),
+                position: Position::default(),
+                zone: String::new(),
+                index: 1,
+                uber_state: UberState::from_parts("1", "1").unwrap(),
+            }),
+            Node::Quest(Quest {
+                identifier: String::from("Quest"),
+                position: Position::default(),
+                zone: String::new(),
+                index: 2,
+                uber_state: quest.clone(),
+            }),
+        ];
+        let graph = Graph { nodes, requirements, ..Graph::default() };
+        let player = Player::default();
+
+        // quests only ever progress, so later stages count for the ones before
+        for (progress, met) in [(2, false), (3, true), (4, true)] {
+            let mut extra_states = FxHashMap::default();
+            extra_states.insert(quest.identifier.clone(), UberValue::Int(progress));
+            let (reached, _) = graph.reached_and_progressions(&player, &graph.nodes[0], &extra_states).unwrap();
+            assert_eq!(reached.iter().any(|node| node.index() == 1), met, "quest at {}", progress);
+        }
+    }
+
+    #[test]
+    fn refill_costs() {
+        let mut requirements = CompiledRequirements::default();
+        let blaze = Requirement::EnergySkill(Skill::Blaze, 1.0);
+        let nodes = vec![
+            anchor(0, vec![], vec![(1, Requirement::Or(vec![Requirement::Damage(10.0), blaze.clone()]))], &mut requirements),
+            anchor(1, vec![(RefillType::Health(1.0), blaze)], vec![(2, Requirement::Free)], &mut requirements),
+            anchor(2, vec![], vec![], &mut requirements),
+        ];
+        let graph = Graph { nodes, requirements, ..Graph::default() };
+        let mut player = Player::default();
+        player.inventory.grant(Item::Resource(Resource::Health), 6);
+        player.inventory.grant(Item::Resource(Resource::Energy), 4);
+        player.inventory.grant(Item::Skill(Skill::Blaze), 1);
+
+        // only the orbs that can afford the refill pay for it
+        let mut reach = IncrementalReach::new(&graph, &graph.nodes[0]);
+        reach.update(&player, &FxHashMap::default());
+        assert_eq!(reach.world_state[&1].len(), 2);
+        assert!(!reach.world_state[&2].is_empty());
+        assert!(reach.world_state[&2].iter().all(|orbs| orbs.energy >= 0.0));
+        // the orbs that can't afford it still get through without the refill
+        assert!(reach.world_state[&2].contains(&Orbs { health: 20.0, energy: 2.0 }));
+    }
+
+    #[test]
+    fn incremental_reach() {
+        let graph = &lexer::parse_logic(&PathBuf::from("areas.wotw"), &PathBuf::from("loc_data.csv"), &PathBuf::from("state_data.csv"), &Pathsets::default(), false).unwrap();
+        let spawn = graph.find_spawn("MarshSpawn.Main").unwrap();
+        let mut player = Player::default();
+        player.spawn(&Settings::default());
+        let mut uber_states = FxHashMap::default();
+
+        let mut reach = IncrementalReach::new(graph, spawn);
+        let items = [
+            Item::Skill(Skill::Sword), Item::Skill(Skill::DoubleJump), Item::Resource(Resource::Keystone), Item::Resource(Resource::Keystone),
+            Item::Skill(Skill::Dash), Item::Resource(Resource::Health), Item::Skill(Skill::Bash), Item::Skill(Skill::Grapple),
+            Item::Teleporter(Teleporter::Glades), Item::Shard(Shard::TripleJump), Item::Resource(Resource::Energy), Item::Skill(Skill::Launch),
+        ];
+        for item in items {
+            player.inventory.grant(item, 1);
+            reach.update(&player, &uber_states);
+
+            let mut incremental = reach.reached().iter().map(|node| node.index()).collect::<Vec<_>>();
+            let mut fresh = graph.reached_locations(&player, spawn, &uber_states).unwrap().iter().map(|node| node.index()).collect::<Vec<_>>();
+            incremental.sort_unstable();
+            fresh.sort_unstable();
+            assert_eq!(incremental, fresh);
+
+            let unmet = reach.progressions();
+            assert!(unmet.iter().all(|(requirement, best_orbs)| best_orbs.iter().all(|orbs| requirement.is_met(&player, &reach.states, *orbs).is_none())));
+        }
+
+        uber_states.insert(UberIdentifier::from_parts("21786", "25095").unwrap(), UberValue::Bool(true));
+        reach.update(&player, &uber_states);
+        assert_eq!(reach.reached().len(), graph.reached_locations(&player, spawn, &uber_states).unwrap().len());
     }
 }
@@ -2,7 +2,7 @@ use std::convert::TryFrom;
 
 use smallvec::{SmallVec, smallvec};
 
-use crate::inventory::{Inventory, Item};
+use crate::inventory::{Inventory, Item, PlayerInventory};
 use crate::util::{
     Pathset, Pathsets, Resource, Skill, Shard,
     orbs::{self, Orbs},
@@ -11,7 +11,7 @@ use crate::util::{
 
 #[derive(Debug, Default, Clone)]
 pub struct Player {
-    pub inventory: Inventory,
+    pub inventory: PlayerInventory,
     pub pathsets: Pathsets,
     pub hard: bool,
 }
@@ -28,13 +28,13 @@ impl Player {
     }
 
     pub fn max_energy(&self) -> f32 {
-        let mut energy = f32::from(self.inventory.get(&Item::Resource(Resource::Energy))) * 0.5;
-        if self.pathsets.contains(Pathset::Gorlek) && self.inventory.has(&Item::Shard(Shard::Energy), 1) { energy += 1.0; }
+        let mut energy = f32::from(self.inventory.resource(Resource::Energy)) * 0.5;
+        if self.pathsets.contains(Pathset::Gorlek) && self.inventory.has_shard(Shard::Energy) { energy += 1.0; }
         energy
     }
     pub fn max_health(&self) -> f32 {
-        let mut health = f32::from(self.inventory.get(&Item::Resource(Resource::Health)) * 5);
-        if self.pathsets.contains(Pathset::Gorlek) && self.inventory.has(&Item::Shard(Shard::Vitality), 1) { health += 10.0; }
+        let mut health = f32::from(self.inventory.resource(Resource::Health) * 5);
+        if self.pathsets.contains(Pathset::Gorlek) && self.inventory.has_shard(Shard::Vitality) { health += 10.0; }
         health
     }
     pub fn max_orbs(&self) -> Orbs {
@@ -45,13 +45,13 @@ impl Player {
     }
     fn cap_orbs(&self, mut orbs: SmallVec<[Orbs; 3]>, checkpoint: bool) -> SmallVec<[Orbs; 3]>{
         let max_health = if checkpoint {
-            f32::from(self.inventory.get(&Item::Resource(Resource::Health)) * 5)  // checkpoint don't fill orbs given by the Vitality shard (but they do energy from the energy shard...)
+            f32::from(self.inventory.resource(Resource::Health) * 5)  // checkpoint don't fill orbs given by the Vitality shard (but they do energy from the energy shard...)
         } else {
             self.max_health()
         };
         let max_energy = self.max_energy();
 
-        if self.pathsets.contains(Pathset::Unsafe) && self.inventory.has(&Item::Shard(Shard::Overflow), 1) {
+        if self.pathsets.contains(Pathset::Unsafe) && self.inventory.has_shard(Shard::Overflow) {
             for orbs in &mut orbs {
                 if orbs.health > max_health {
                     orbs.energy += orbs.health - max_health;
@@ -124,29 +124,29 @@ impl Player {
 
         damage_mod += 0.25 * f32::from(self.inventory.get(&Item::Skill(Skill::AncestralLight)));
 
-        let mut slots = self.inventory.get(&Item::Resource(Resource::ShardSlot));
+        let mut slots = self.inventory.resource(Resource::ShardSlot);
         let mut splinter = false;
 
-        if flying_target && slots > 0 && is_unsafe && self.inventory.has(&Item::Shard(Shard::Wingclip), 1) { damage_mod += 1.0; slots -= 1; }
-        if slots > 0 && is_unsafe && bow && self.inventory.has(&Item::Shard(Shard::Splinter), 1) { splinter = true; slots -= 1; }
-        if slots > 0 && is_unsafe && self.inventory.has(&Item::Shard(Shard::SpiritSurge), 1) { damage_mod += f32::from(self.inventory.get(&Item::SpiritLight(1)) / 10000); slots -= 1; }
-        if slots > 0 && is_unsafe && self.inventory.has(&Item::Shard(Shard::LastStand), 1) { damage_mod += 0.2; slots -= 1; }
-        if slots > 0 && is_unsafe && self.inventory.has(&Item::Shard(Shard::Reckless), 1) { damage_mod += 0.15; slots -= 1; }
-        if slots > 0 && is_unsafe && self.inventory.has(&Item::Shard(Shard::Lifeforce), 1) { damage_mod += 0.1; slots -= 1; }
-        if slots > 0 && is_unsafe && self.inventory.has(&Item::Shard(Shard::Finesse), 1) { damage_mod += 0.05; }
+        if flying_target && slots > 0 && is_unsafe && self.inventory.has_shard(Shard::Wingclip) { damage_mod += 1.0; slots -= 1; }
+        if slots > 0 && is_unsafe && bow && self.inventory.has_shard(Shard::Splinter) { splinter = true; slots -= 1; }
+        if slots > 0 && is_unsafe && self.inventory.has_shard(Shard::SpiritSurge) { damage_mod += f32::from(self.inventory.spirit_light() / 10000); slots -= 1; }
+        if slots > 0 && is_unsafe && self.inventory.has_shard(Shard::LastStand) { damage_mod += 0.2; slots -= 1; }
+        if slots > 0 && is_unsafe && self.inventory.has_shard(Shard::Reckless) { damage_mod += 0.15; slots -= 1; }
+        if slots > 0 && is_unsafe && self.inventory.has_shard(Shard::Lifeforce) { damage_mod += 0.1; slots -= 1; }
+        if slots > 0 && is_unsafe && self.inventory.has_shard(Shard::Finesse) { damage_mod += 0.05; }
         if splinter { damage_mod *= 1.5; }  // The splinter debuff stacks multiplicatively even though other buffs stack additively
         damage_mod
     }
     pub fn defense_mod(&self) -> f32 {
         let mut defense_mod = 1.0;
-        if self.pathsets.contains(Pathset::Gorlek) && self.inventory.has(&Item::Shard(Shard::Resilience), 1) { defense_mod = 0.9; }
+        if self.pathsets.contains(Pathset::Gorlek) && self.inventory.has_shard(Shard::Resilience) { defense_mod = 0.9; }
         if self.hard { defense_mod *= 2.0; }
         defense_mod
     }
     pub fn energy_mod(&self) -> f32 {
         let mut energy_mod = 1.0;
         if !self.pathsets.contains(Pathset::Unsafe) { energy_mod *= 2.0; }
-        else if self.inventory.has(&Item::Shard(Shard::Overcharge), 1) { energy_mod *= 0.5; }
+        else if self.inventory.has_shard(Shard::Overcharge) { energy_mod *= 0.5; }
         energy_mod
     }
 
@@ -206,7 +206,7 @@ impl Player {
     where W: IntoIterator<Item=Skill>,
     {
         for weapon in weapons {
-            if self.inventory.has(&Item::Skill(weapon), 1) {
+            if self.inventory.has_skill(weapon) {
                 return Some(weapon);
             }
         }
@@ -229,7 +229,7 @@ impl Player {
 
         for weapon in weapons {
             progression_weapons.push(weapon);
-            if self.inventory.has(&Item::Skill(weapon), 1) {
+            if self.inventory.has_skill(weapon) {
                 break;
             }
         }
@@ -1,3 +1,5 @@
+pub mod compiled;
+
 use std::convert::TryFrom;
 
 use rustc_hash::FxHashSet;
@@ -37,9 +39,9 @@ impl Requirement {
                 energy: -cost,
                 ..Orbs::default()
             }
-        ])} else if player.pathsets.contains(Pathset::Unsafe) && player.inventory.has(&Item::Shard(Shard::LifePact), 1) && orbs.energy + orbs.health > cost { Some(smallvec![
+        ])} else if player.pathsets.contains(Pathset::Unsafe) && player.inventory.has_shard(Shard::LifePact) && orbs.energy + orbs.health > cost { Some(smallvec![
             Orbs {
-                health: cost - orbs.energy,
+                health: orbs.energy - cost,
                 energy: -orbs.energy,
             }
         ])} else { None }
@@ -50,22 +52,22 @@ impl Requirement {
             Requirement::Free => return Some(smallvec![Orbs::default()]),
             Requirement::Impossible => return None,
             Requirement::Skill(skill) =>
-                if player.inventory.has(&Item::Skill(*skill), 1) { return Some(smallvec![Orbs::default()]); },
+                if player.inventory.has_skill(*skill) { return Some(smallvec![Orbs::default()]); },
             Requirement::EnergySkill(skill, amount) =>
-                if player.inventory.has(&Item::Skill(*skill), 1) {
+                if player.inventory.has_skill(*skill) {
                     let cost = player.use_cost(*skill) * *amount;
                     return Requirement::cost_is_met(cost, player, orbs);
                 }
             Requirement::SpiritLight(amount) =>
-                if player.inventory.has(&Item::SpiritLight(1), *amount) { return Some(smallvec![Orbs::default()]); },
+                if player.inventory.spirit_light() >= *amount { return Some(smallvec![Orbs::default()]); },
             Requirement::Resource(resource, amount) =>
-                if player.inventory.has(&Item::Resource(*resource), *amount) { return Some(smallvec![Orbs::default()]); },
+                if player.inventory.resource(*resource) >= *amount { return Some(smallvec![Orbs::default()]); },
             Requirement::Shard(shard) =>
-                if player.inventory.has(&Item::Shard(*shard), 1) { return Some(smallvec![Orbs::default()]); },
+                if player.inventory.has_shard(*shard) { return Some(smallvec![Orbs::default()]); },
             Requirement::Teleporter(teleporter) =>
-                if player.inventory.has(&Item::Teleporter(*teleporter), 1) { return Some(smallvec![Orbs::default()]); },
+                if player.inventory.has_teleporter(*teleporter) { return Some(smallvec![Orbs::default()]); },
             Requirement::Water =>
-                if player.inventory.has(&Item::Water, 1) { return Some(smallvec![Orbs::default()]); },
+                if player.inventory.has_water() { return Some(smallvec![Orbs::default()]); },
             Requirement::State(state) =>
                 if states.contains(state) { return Some(smallvec![Orbs::default()]); },
             Requirement::Damage(amount) => {
@@ -76,7 +78,7 @@ impl Requirement {
                         ..Orbs::default()
                     }
                 ])}
-                else if player.inventory.has(&Item::Skill(Skill::Regenerate), 1) {
+                else if player.inventory.has_skill(Skill::Regenerate) {
                     let max_health = player.max_health();
                     if max_health > cost {
                         let regens = ((cost - orbs.health) / 30.0).ceil();
@@ -95,7 +97,7 @@ impl Requirement {
                 if orbs.health > cost {
                     return Some(smallvec![Orbs::default()]);
                 }
-                else if player.inventory.has(&Item::Skill(Skill::Regenerate), 1) {
+                else if player.inventory.has_skill(Skill::Regenerate) {
                     let max_health = player.max_health();
                     if max_health > cost {
                         let regens = ((cost - orbs.health) / 30.0).ceil();
@@ -137,9 +139,9 @@ impl Requirement {
 
                         if enemy.aerial() { aerial = true; }
                         if enemy.dangerous() { dangerous = true; }
-                        if !player.pathsets.contains(Pathset::Unsafe) && enemy == &Enemy::Bat && !player.inventory.has(&Item::Skill(Skill::Bash), 1) { return None; }
+                        if !player.pathsets.contains(Pathset::Unsafe) && enemy == &Enemy::Bat && !player.inventory.has_skill(Skill::Bash) { return None; }
                         if enemy == &Enemy::Sandworm {
-                            if player.inventory.has(&Item::Skill(Skill::Burrow), 1) { continue; }
+                            if player.inventory.has_skill(Skill::Burrow) { continue; }
                             else if !player.pathsets.contains(Pathset::Unsafe) { return None; }
                         }
 
@@ -158,15 +160,15 @@ impl Requirement {
                     }
 
                     if !player.pathsets.contains(Pathset::Unsafe) && aerial && !(
-                        player.inventory.has(&Item::Skill(Skill::DoubleJump), 1) ||
-                        player.inventory.has(&Item::Skill(Skill::Launch), 1) ||
-                        player.pathsets.contains(Pathset::Gorlek) && player.inventory.has(&Item::Skill(Skill::Bash), 1)
+                        player.inventory.has_skill(Skill::DoubleJump) ||
+                        player.inventory.has_skill(Skill::Launch) ||
+                        player.pathsets.contains(Pathset::Gorlek) && player.inventory.has_skill(Skill::Bash)
                     ) { return None; }
                     if !player.pathsets.contains(Pathset::Unsafe) && dangerous && !(
-                        player.inventory.has(&Item::Skill(Skill::DoubleJump), 1) ||
-                        player.inventory.has(&Item::Skill(Skill::Dash), 1) ||
-                        player.inventory.has(&Item::Skill(Skill::Bash), 1) ||
-                        player.inventory.has(&Item::Skill(Skill::Launch), 1)
+                        player.inventory.has_skill(Skill::DoubleJump) ||
+                        player.inventory.has_skill(Skill::Dash) ||
+                        player.inventory.has_skill(Skill::Bash) ||
+                        player.inventory.has_skill(Skill::Launch)
                     ) { return None; }
 
                     let cost = orbs.energy - energy;
@@ -174,7 +176,7 @@ impl Requirement {
                 }
             },
             Requirement::ShurikenBreak(health) =>
-                if player.inventory.has(&Item::Skill(Skill::Shuriken), 1) {
+                if player.inventory.has_skill(Skill::Shuriken) {
                     let clip_mod = if player.pathsets.contains(Pathset::Unsafe) { 2.0 } else { 3.0 };
                     let cost = player.destroy_cost(*health, Skill::Shuriken, false) * clip_mod;
                     return Requirement::cost_is_met(cost, player, orbs);
@@ -197,6 +199,7 @@ impl Requirement {
                     best_orbs = orbs::both(&best_orbs, &orbcosts);
                     best_orbs.retain(|orbs| orbs.health > 0.0 && orbs.energy >= 0.0);
                 }
+                if best_orbs.is_empty() { return None; }
 
                 let cost = orbs::both_single(&best_orbs, Orbs { health: -orbs.health, energy: -orbs.energy });
                 return Some(cost);
@@ -228,7 +231,7 @@ impl Requirement {
     fn needed_for_cost(cost: f32, player: &Player) -> Itemset {
         let mut itemsets = vec![(Inventory::default(), Orbs{ energy: -cost, ..Orbs::default() })];
 
-        if player.pathsets.contains(Pathset::Unsafe) && cost > 0.0 && !player.inventory.has(&Item::Shard(Shard::Overcharge), 1) {
+        if player.pathsets.contains(Pathset::Unsafe) && cost > 0.0 && !player.inventory.has_shard(Shard::Overcharge) {
             itemsets.push((Inventory::from(Item::Shard(Shard::Overcharge)), Orbs{ energy: -cost / 2.0, ..Orbs::default() }));
         }
 
@@ -330,7 +333,7 @@ impl Requirement {
 
                 itemsets.append(&mut Requirement::needed_for_damage(cost, player));
 
-                if player.pathsets.contains(Pathset::Gorlek) && !player.inventory.has(&Item::Shard(Shard::Resilience), 1) {
+                if player.pathsets.contains(Pathset::Gorlek) && !player.inventory.has_shard(Shard::Resilience) {
                     let resilience_cost = cost * 0.9;
 
                     let mut resilience_sets = Requirement::needed_for_damage(resilience_cost, player);
@@ -393,7 +396,7 @@ impl Requirement {
                     player.shield_progression_weapons()
                 } else { smallvec![Skill::Hammer] };
                 let use_burrow: SmallVec<[_; 2]> = if burrow {
-                    if !player.pathsets.contains(Pathset::Unsafe) || player.inventory.has(&Item::Skill(Skill::Burrow), 1) {
+                    if !player.pathsets.contains(Pathset::Unsafe) || player.inventory.has_skill(Skill::Burrow) {
                         smallvec![true]
                     } else {
                         smallvec![true, false]
@@ -464,7 +467,7 @@ impl Requirement {
                         itemsets = Requirement::combine_itemset_items(itemsets, &evasion_skills);
                     }
                 }
-                if !player.pathsets.contains(Pathset::Unsafe) && bash && !player.inventory.has(&Item::Skill(Skill::Bash), 1) {
+                if !player.pathsets.contains(Pathset::Unsafe) && bash && !player.inventory.has_skill(Skill::Bash) {
                     Requirement::combine_itemset_item(&mut itemsets, &Item::Skill(Skill::Bash));
                 }
 
@@ -523,6 +526,9 @@ mod tests {
         assert!(req.is_met(&player, &states, player.max_orbs()).is_none());
         player.pathsets.add(Pathset::Unsafe);
         assert_eq!(req.is_met(&player, &states, player.max_orbs()), Some(smallvec![Orbs { energy: -1.0, ..orbs }]));
+        player.inventory.grant(Item::Shard(Shard::LifePact), 1);
+        assert_eq!(req.is_met(&player, &states, Orbs { health: 30.0, energy: 0.0 }), Some(smallvec![Orbs { health: -1.0, ..orbs }]));
+        player.inventory.remove(&Item::Shard(Shard::LifePact), 1);
         player.pathsets = Pathsets::default();
         player.inventory.grant(Item::Resource(Resource::Energy), 2);
         assert_eq!(req.is_met(&player, &states, player.max_orbs()), Some(smallvec![Orbs { energy: -2.0, ..orbs }]));
@@ -569,6 +575,9 @@ mod tests {
         player.inventory.grant(Item::Resource(Resource::Energy), 2);
         player.pathsets.add(Pathset::Unsafe);
         assert_eq!(req.is_met(&player, &states, player.max_orbs()), Some(smallvec![Orbs { energy: -1.0, ..orbs }]));
+        player.inventory.grant(Item::Shard(Shard::LifePact), 1);
+        assert_eq!(req.is_met(&player, &states, Orbs { health: 30.0, energy: 0.0 }), Some(smallvec![Orbs { health: -1.0, ..orbs }]));
+        player.inventory.remove(&Item::Shard(Shard::LifePact), 1);
         player.pathsets = Pathsets::default();
         player.inventory.grant(Item::Resource(Resource::Energy), 2);
         assert!(req.is_met(&player, &states, player.max_orbs()).is_none());
@@ -671,6 +680,10 @@ mod tests {
         player.inventory.grant(Item::Skill(Skill::Blaze), 1);
         player.inventory.grant(Item::Resource(Resource::Energy), 2);
         assert_eq!(req.is_met(&player, &states, player.max_orbs()), Some(smallvec![Orbs { health: -20.0, ..orbs }, Orbs { health: -10.0, energy: -1.0 }, Orbs { energy: -2.0, ..orbs }]));
+
+        // running out of health on the last part doesn't count either
+        let req = Requirement::And(vec![Requirement::Skill(Skill::Blaze), Requirement::Damage(30.0)]);
+        assert!(req.is_met(&player, &states, Orbs { health: 30.0, energy: 2.0 }).is_none());
     }
 
     #[test]
@@ -0,0 +1,254 @@
+use rustc_hash::{FxHashMap, FxHashSet};
+use smallvec::{SmallVec, smallvec};
+
+use super::Requirement;
+use crate::world::player::Player;
+use crate::inventory::{Item, ItemMask};
+use crate::util::orbs::Orbs;
+
+#[derive(Debug, Clone)]
+enum Instruction {
+    Free,
+    Impossible,
+    Items(ItemMask),
+    State(usize),
+    // ranges of operands
+    And(usize, usize),
+    Or(usize, usize),
+    // requirements that cost orbs are left to Requirement::is_met, once the range of costless operands they need is met
+    Cost(Requirement, usize, usize),
+}
+
+// Identical requirements share an instruction
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum Key {
+    Free,
+    Impossible,
+    Items(ItemMask),
+    State(usize),
+    Cost(String),
+    And(Vec<usize>),
+    Or(Vec<usize>),
+}
+
+/// The requirements of a whole graph flattened into one array, so that reach checks can evaluate them without walking through nested vectors
+///
+/// Items that don't cost orbs are checked together as one [`ItemMask`] wherever they have to be met together.
+/// Requirements that cost orbs still go through [`Requirement::is_met`], but only after the costless parts they need are met
+#[derive(Debug, Default)]
+pub struct CompiledRequirements {
+    instructions: Vec<Instruction>,
+    operands: Vec<usize>,
+    costless: Vec<bool>,
+    contained_states: Vec<Vec<usize>>,
+    keys: FxHashMap<Key, usize>,
+}
+impl CompiledRequirements {
+    fn item_mask(requirement: &Requirement) -> Option<ItemMask> {
+        let mut mask = ItemMask::default();
+        match requirement {
+            Requirement::Skill(skill) => mask.require(&Item::Skill(*skill), 1),
+            Requirement::SpiritLight(amount) => mask.require(&Item::SpiritLight(1), *amount),
+            Requirement::Resource(resource, amount) => mask.require(&Item::Resource(*resource), *amount),
+            Requirement::Shard(shard) => mask.require(&Item::Shard(*shard), 1),
+            Requirement::Teleporter(teleporter) => mask.require(&Item::Teleporter(*teleporter), 1),
+            Requirement::Water => mask.require(&Item::Water, 1),
+            _ => return None,
+        }
+        Some(mask)
+    }
+
+    fn add(&mut self, key: Key, instruction: Instruction, contained_states: Vec<usize>) -> usize {
+        if let Some(&index) = self.keys.get(&key) {
+            return index;
+        }
+        let costless = match &instruction {
+            Instruction::Cost(..) => false,
+            Instruction::And(start, end) | Instruction::Or(start, end) => self.operands[*start..*end].iter().all(|&operand| self.costless[operand]),
+            _ => true,
+        };
+
+        let index = self.instructions.len();
+        self.instructions.push(instruction);
+        self.costless.push(costless);
+        self.contained_states.push(contained_states);
+        self.keys.insert(key, index);
+        index
+    }
+    fn add_operands(&mut self, key: fn(Vec<usize>) -> Key, requirement: &Requirement, operands: Vec<usize>) -> usize {
+        if let Some(&index) = self.keys.get(&key(operands.clone())) {
+            return index;
+        }
+        let mut contained_states = operands.iter().flat_map(|&operand| self.contained_states[operand].iter().copied()).collect::<Vec<_>>();
+        contained_states.sort_unstable();
+        contained_states.dedup();
+
+        let start = self.operands.len();
+        let instruction = if operands.iter().all(|&operand| self.costless[operand]) {
+            self.operands.extend_from_slice(&operands);
+            match requirement {
+                Requirement::And(_) => Instruction::And(start, self.operands.len()),
+                _ => Instruction::Or(start, self.operands.len()),
+            }
+        } else {
+            // an or might be met through any of its operands, so only the operands of an and are always needed
+            if matches!(requirement, Requirement::And(_)) {
+                let costless = &self.costless;
+                self.operands.extend(operands.iter().copied().filter(|&operand| costless[operand]));
+            }
+            Instruction::Cost(requirement.clone(), start, self.operands.len())
+        };
+        self.add(key(operands), instruction, contained_states)
+    }
+
+    fn flatten_and<'a>(requirement: &'a Requirement, flat: &mut Vec<&'a Requirement>) {
+        match requirement {
+            Requirement::And(ands) => ands.iter().for_each(|and| CompiledRequirements::flatten_and(and, flat)),
+            _ => flat.push(requirement),
+        }
+    }
+
+    /// Adds a requirement and returns its index, reusing what has been compiled before
+    pub fn compile(&mut self, requirement: &Requirement) -> usize {
+        if let Some(mask) = CompiledRequirements::item_mask(requirement) {
+            return self.add(Key::Items(mask.clone()), Instruction::Items(mask), Vec::new());
+        }
+        match requirement {
+            Requirement::Free => self.add(Key::Free, Instruction::Free, Vec::new()),
+            Requirement::Impossible => self.add(Key::Impossible, Instruction::Impossible, Vec::new()),
+            Requirement::State(state) => self.add(Key::State(*state), Instruction::State(*state), vec![*state]),
+            Requirement::And(_) => {
+                let mut flat = Vec::new();
+                CompiledRequirements::flatten_and(requirement, &mut flat);
+
+                let mut mask = ItemMask::default();
+                let mut operands = Vec::with_capacity(flat.len());
+                for and in flat {
+                    match CompiledRequirements::item_mask(and) {
+                        Some(item_mask) => mask.merge(&item_mask),
+                        None => operands.push(self.compile(and)),
+                    }
+                }
+                if operands.iter().any(|&operand| matches!(self.instructions[operand], Instruction::Impossible)) {
+                    return self.compile(&Requirement::Impossible);
+                }
+                operands.retain(|&operand| !matches!(self.instructions[operand], Instruction::Free));
+                if !mask.is_empty() {
+                    operands.insert(0, self.add(Key::Items(mask.clone()), Instruction::Items(mask), Vec::new()));
+                }
+                // cheap checks first so that failing ones skip the orb calculations
+                operands.sort_by_key(|&operand| !self.costless[operand]);
+
+                match operands.len() {
+                    0 => self.compile(&Requirement::Free),
+                    1 => operands[0],
+                    _ => self.add_operands(Key::And, requirement, operands),
+                }
+            },
+            Requirement::Or(ors) => {
+                // nested ors stay as they are since orbs::either depends on the order it combines orbs in
+                let mut operands = ors.iter().map(|or| self.compile(or)).collect::<Vec<_>>();
+                if operands.iter().any(|&operand| matches!(self.instructions[operand], Instruction::Free)) {
+                    return self.compile(&Requirement::Free);
+                }
+                operands.retain(|&operand| !matches!(self.instructions[operand], Instruction::Impossible));
+
+                match operands.len() {
+                    0 => self.compile(&Requirement::Impossible),
+                    1 => operands[0],
+                    _ => self.add_operands(Key::Or, requirement, operands),
+                }
+            },
+            _ => {
+                let end = self.operands.len();
+                self.add(Key::Cost(format!("{:?}", requirement)), Instruction::Cost(requirement.clone(), end, end), Vec::new())
+            },
+        }
+    }
+
+    /// The states used anywhere in the requirement, sorted and without duplicates
+    #[inline]
+    pub fn contained_states(&self, index: usize) -> &[usize] {
+        &self.contained_states[index]
+    }
+
+    // Only valid on costless instructions
+    fn check(&self, index: usize, player: &Player, states: &FxHashSet<usize>) -> bool {
+        match &self.instructions[index] {
+            Instruction::Free => true,
+            Instruction::Items(mask) => player.inventory.contains_mask(mask),
+            Instruction::State(state) => states.contains(state),
+            Instruction::And(start, end) => self.operands[*start..*end].iter().all(|&operand| self.check(operand, player, states)),
+            Instruction::Or(start, end) => self.operands[*start..*end].iter().any(|&operand| self.check(operand, player, states)),
+            Instruction::Impossible | Instruction::Cost(..) => false,
+        }
+    }
+
+    /// Same as [`Requirement::is_met`] on the requirement that was compiled to this index
+    pub fn is_met(&self, index: usize, player: &Player, states: &FxHashSet<usize>, orbs: Orbs) -> Option<SmallVec<[Orbs; 3]>> {
+        match &self.instructions[index] {
+            Instruction::Cost(requirement, start, end) => {
+                if self.operands[*start..*end].iter().all(|&operand| self.check(operand, player, states)) {
+                    requirement.is_met(player, states, orbs)
+                } else { None }
+            },
+            _ => if self.check(index, player, states) { Some(smallvec![Orbs::default()]) } else { None },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use crate::lexer;
+    use crate::world::graph::Node;
+    use crate::util::{Pathsets, Resource, Skill, Shard, settings::Settings};
+
+    #[test]
+    fn compiled_requirements() {
+        let mut compiled = CompiledRequirements::default();
+        let requirement = Requirement::And(vec![
+            Requirement::Skill(Skill::Bash),
+            Requirement::And(vec![Requirement::Skill(Skill::Glide), Requirement::Resource(Resource::Keystone, 2)]),
+            Requirement::Or(vec![Requirement::State(4), Requirement::Damage(10.0)]),
+            Requirement::Free,
+        ]);
+        let index = compiled.compile(&requirement);
+        assert_eq!(compiled.compile(&requirement.clone()), index);
+        assert_eq!(compiled.contained_states(index), [4]);
+        // the orb costs are left to the requirement, after checking the items it needs
+        assert!(matches!(compiled.instructions[index], Instruction::Cost(_, start, end) if end - start == 1));
+        let instructions = compiled.instructions.len();
+        compiled.compile(&Requirement::Or(vec![Requirement::State(4), Requirement::Damage(10.0)]));
+        assert_eq!(compiled.instructions.len(), instructions);
+        let impossible = compiled.compile(&Requirement::And(vec![requirement, Requirement::Impossible]));
+        assert!(matches!(compiled.instructions[impossible], Instruction::Impossible));
+
+        let graph = &lexer::parse_logic(&PathBuf::from("areas.wotw"), &PathBuf::from("loc_data.csv"), &PathBuf::from("state_data.csv"), &Pathsets::default(), false).unwrap();
+        let connections = graph.nodes.iter().filter_map(|node| match node {
+            Node::Anchor(anchor) => Some(&anchor.connections),
+            _ => None,
+        }).flatten().collect::<Vec<_>>();
+        let unique = connections.iter().map(|connection| connection.compiled).collect::<FxHashSet<_>>();
+        assert!(unique.len() < connections.len());
+
+        let mut player = Player::default();
+        player.spawn(&Settings::default());
+        let states = [18, 200, 350].iter().copied().collect();
+        let items = [Item::Skill(Skill::DoubleJump), Item::Skill(Skill::Sword), Item::Resource(Resource::Energy), Item::Skill(Skill::Bash), Item::Shard(Shard::TripleJump), Item::Skill(Skill::Grenade)];
+        for item in items {
+            player.inventory.grant(item, 1);
+            for orbs in [player.max_orbs(), Orbs { health: 5.0, energy: 1.0 }] {
+                for connection in &connections {
+                    let expected = connection.requirement.is_met(&player, &states, orbs);
+                    assert_eq!(graph.requirements.is_met(connection.compiled, &player, &states, orbs), expected, "{:?}", connection.requirement);
+                    let mut states = connection.requirement.contained_states();
+                    states.sort_unstable();
+                    states.dedup();
+                    assert_eq!(graph.requirements.contained_states(connection.compiled), &states[..]);
+                }
+            }
+        }
+    }
+}